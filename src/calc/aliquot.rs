@@ -0,0 +1,200 @@
+use calc::sum_of_proper_divisors;
+use prime::arithmetic::divisor_sums;
+
+/// Classification of a number by comparing it to the sum of its proper divisors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    /// The proper divisors add up to less than the number, like 8 (1 + 2 + 4 = 7).
+    Deficient,
+    /// The proper divisors add up to the number, like 28 (1 + 2 + 4 + 7 + 14 = 28).
+    Perfect,
+    /// The proper divisors add up to more than the number, like 12 (1 + 2 + 3 + 4 + 6 = 16).
+    Abundant
+}
+
+/// Classifies n as deficient, perfect or abundant. Panics if n is 0, which is divisible by
+/// every positive number and so has no finite sum of proper divisors.
+pub fn classify(n: u64) -> Classification {
+    assert!(n > 0, "0 cannot be classified by its proper divisors");
+    classify_with_sum(n, sum_of_proper_divisors(n))
+}
+
+fn classify_with_sum(n: u64, aliquot_sum: u64) -> Classification {
+    if aliquot_sum < n {
+        Classification::Deficient
+    } else if aliquot_sum == n {
+        Classification::Perfect
+    } else {
+        Classification::Abundant
+    }
+}
+
+/// Returns the sum of the proper divisors of every n in 0..=limit. The entry for 0 is 0.
+pub fn aliquot_sums(limit: usize) -> Vec<u64> {
+    let mut sums = divisor_sums(limit);
+    for (n, sum) in sums.iter_mut().enumerate() {
+        *sum -= n as u64;
+    }
+    sums
+}
+
+/// Returns the amicable pairs (a, b) with a < b < limit, ordered by a. The numbers in an
+/// amicable pair are different, and each is the sum of the proper divisors of the other.
+/// The values start: (220, 284), (1184, 1210), (2620, 2924), (5020, 5564), ...
+pub fn amicable_pairs(limit: usize) -> impl Iterator<Item = (u64, u64)> {
+    let sums = aliquot_sums(limit.saturating_sub(1));
+    (2..sums.len()).filter_map(move |a| {
+        let b = sums[a];
+        if b > a as u64 && b < sums.len() as u64 && sums[b as usize] == a as u64 {
+            Some((a as u64, b))
+        } else {
+            None
+        }
+    })
+}
+
+/// An iterator over the aliquot sequence starting at n, where each term is the sum of the
+/// proper divisors of the previous one. For example 12, 16, 15, 9, 4, 3, 1, 0.
+/// The sequence stops after reaching 0. Perfect, amicable and sociable numbers cycle
+/// forever, so use `take` or look for repeats if the start might be one of those.
+pub struct AliquotSequence {
+    next: Option<u64>
+}
+
+impl AliquotSequence {
+    pub fn new(n: u64) -> Self {
+        AliquotSequence { next: Some(n) }
+    }
+}
+
+impl Iterator for AliquotSequence {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let n = self.next?;
+        self.next = if n == 0 { None } else { Some(sum_of_proper_divisors(n)) };
+        Some(n)
+    }
+}
+
+/// A bitset recording which numbers up to a limit can be written as the sum of two
+/// (not necessarily different) abundant numbers, as needed by Project Euler 23.
+pub struct AbundantSums {
+    bits: Vec<u64>,
+    limit: usize
+}
+
+impl AbundantSums {
+    pub fn new(limit: usize) -> Self {
+        let sums = aliquot_sums(limit);
+        let words = limit / 64 + 1;
+
+        let mut abundant = vec![0_u64; words];
+        for n in 1..limit + 1 {
+            if classify_with_sum(n as u64, sums[n]) == Classification::Abundant {
+                abundant[n / 64] |= 1 << (n % 64);
+            }
+        }
+
+        // For each abundant a, every abundant b gives the sum a + b, so OR in the whole
+        // abundant bitset shifted left by a. This works a word at a time rather than a pair
+        // at a time.
+        let mut bits = vec![0_u64; words];
+        for a in (1..limit + 1).filter(|&a| abundant[a / 64] & (1 << (a % 64)) != 0) {
+            let word_shift = a / 64;
+            let bit_shift = a % 64;
+            for i in (word_shift..words).rev() {
+                let src = i - word_shift;
+                let mut word = abundant[src] << bit_shift;
+                if bit_shift != 0 && src > 0 {
+                    word |= abundant[src - 1] >> (64 - bit_shift);
+                }
+                bits[i] |= word;
+            }
+        }
+
+        AbundantSums { bits, limit }
+    }
+
+    /// Returns true if n is the sum of two abundant numbers. n must be no more than the limit.
+    #[inline]
+    pub fn contains(&self, n: usize) -> bool {
+        assert!(n <= self.limit, "{} is beyond the limit of {}", n, self.limit);
+        self.bits[n / 64] & (1 << (n % 64)) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_works() {
+        assert_eq!(classify(1), Classification::Deficient);
+        assert_eq!(classify(8), Classification::Deficient);
+        assert_eq!(classify(6), Classification::Perfect);
+        assert_eq!(classify(28), Classification::Perfect);
+        assert_eq!(classify(8128), Classification::Perfect);
+        assert_eq!(classify(12), Classification::Abundant);
+        assert_eq!(classify(945), Classification::Abundant);
+    }
+
+    #[test]
+    #[should_panic]
+    fn classify_panics_for_zero() {
+        classify(0);
+    }
+
+    #[test]
+    fn aliquot_sums_works() {
+        assert_eq!(aliquot_sums(12), vec![0, 0, 1, 1, 3, 1, 6, 1, 7, 4, 8, 1, 16]);
+
+        let sums = aliquot_sums(1000);
+        for (n, &sum) in sums.iter().enumerate().skip(1) {
+            assert_eq!(sum, sum_of_proper_divisors(n as u64));
+        }
+    }
+
+    #[test]
+    fn amicable_pairs_works() {
+        assert_eq!(amicable_pairs(10_000).collect::<Vec<_>>(),
+                   vec![(220, 284), (1184, 1210), (2620, 2924), (5020, 5564), (6232, 6368)]);
+        // The second number must be below the limit too.
+        assert_eq!(amicable_pairs(284).count(), 0);
+        assert_eq!(amicable_pairs(285).count(), 1);
+
+        // Project Euler 21.
+        let sum = amicable_pairs(10_000).map(|(a, b)| a + b).sum::<u64>();
+        assert_eq!(sum, 31_626);
+    }
+
+    #[test]
+    fn aliquot_sequence_works() {
+        assert_eq!(AliquotSequence::new(12).collect::<Vec<_>>(), vec![12, 16, 15, 9, 4, 3, 1, 0]);
+        assert_eq!(AliquotSequence::new(0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(AliquotSequence::new(28).take(3).collect::<Vec<_>>(), vec![28, 28, 28]);
+        assert_eq!(AliquotSequence::new(220).take(4).collect::<Vec<_>>(), vec![220, 284, 220, 284]);
+        // A sociable chain of length 5.
+        assert_eq!(AliquotSequence::new(12_496).take(6).collect::<Vec<_>>(),
+                   vec![12_496, 14_288, 15_472, 14_536, 14_264, 12_496]);
+    }
+
+    #[test]
+    fn abundant_sums_matches_brute_force() {
+        let limit = 2000;
+        let abundant = (1..limit + 1).filter(|&n| classify(n as u64) == Classification::Abundant).collect::<Vec<_>>();
+        let sums = AbundantSums::new(limit);
+
+        for n in 0..limit + 1 {
+            let expected = abundant.iter().any(|&a| a < n && abundant.binary_search(&(n - a)).is_ok());
+            assert_eq!(sums.contains(n), expected, "contains({})", n);
+        }
+    }
+
+    #[test]
+    fn abundant_sums_solves_problem_23() {
+        let sums = AbundantSums::new(28_123);
+        let answer = (1..28_124).filter(|&n| !sums.contains(n)).sum::<usize>();
+        assert_eq!(answer, 4_179_871);
+    }
+}
@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+
+// Collatz lengths (see https://projecteuler.net/problem=14). The length of n counts the
+// terms in its sequence down to 1, including both n and 1, so the length of 1 is 1.
+//
+// `collatz_len` walks the sequence until it reaches a number whose length is already known,
+// then records the lengths of everything it passed on the way. Where those lengths are kept
+// is up to the `CollatzCache`, so different backends can be benchmarked against each other
+// in p014 with the same algorithm.
+//
+// Every function here steps through sequences with `CollatzMap::step`. The plain functions
+// use the usual map, `COLLATZ`, and the methods on `CollatzMap` work for any map.
+
+/// Calculate the Collatz length of a number without referring to any existing cache of
+/// known Collatz lengths. Panics if n is 0 or the sequence goes above u64::MAX.
+pub fn collatz_len_simple(n: u64) -> u64 {
+    checked_collatz_len_simple(n).unwrap_or_else(|| overflowed(n))
+}
+
+/// As `collatz_len_simple`, but returns None if the sequence goes above u64::MAX.
+pub fn checked_collatz_len_simple(n: u64) -> Option<u64> {
+    COLLATZ.len(n)
+}
+
+/// A store of known Collatz lengths for `collatz_len`. A cache may decline to store some
+/// values, for example because they do not fit in its key type; they are then recalculated
+/// when needed.
+pub trait CollatzCache {
+    fn get(&self, n: u64) -> Option<u64>;
+    fn insert(&mut self, n: u64, collatz_len: u64);
+}
+
+/// Calculate the Collatz length of a number, making use of and adding to a cache of known
+/// Collatz lengths. The cache does not need to be seeded with the length of 1. Panics if n
+/// is 0 or the sequence goes above u64::MAX.
+pub fn collatz_len<C: CollatzCache>(n: u64, known_collatzes: &mut C) -> u64 {
+    checked_collatz_len(n, known_collatzes).unwrap_or_else(|| overflowed(n))
+}
+
+/// As `collatz_len`, but returns None if the sequence goes above u64::MAX. Nothing is added
+/// to the cache in that case.
+pub fn checked_collatz_len<C: CollatzCache>(n: u64, known_collatzes: &mut C) -> Option<u64> {
+    COLLATZ.len_cached(n, known_collatzes)
+}
+
+#[cold]
+fn overflowed(n: u64) -> ! {
+    panic!("The Collatz sequence of {} overflows a u64", n)
+}
+
+// HashMaps with any hasher, so this covers FnvHashMap too. Sequences starting below a
+// million go up to about 5.7 * 10^10, so a map keyed by u32 only stores the values which fit.
+macro_rules! impl_collatz_cache_for_hashmap {
+    ($($t:ty),*) => {
+        $(
+            impl<S: BuildHasher> CollatzCache for HashMap<$t, $t, S> {
+                #[inline]
+                fn get(&self, n: u64) -> Option<u64> {
+                    <$t>::try_from(n).ok().and_then(|n| HashMap::get(self, &n)).and_then(|&len| u64::try_from(len).ok())
+                }
+
+                #[inline]
+                fn insert(&mut self, n: u64, collatz_len: u64) {
+                    if let (Some(n), Some(len)) = (<$t>::try_from(n).ok(), <$t>::try_from(collatz_len).ok()) {
+                        HashMap::insert(self, n, len);
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_collatz_cache_for_hashmap!(u32, u64, usize);
+
+/// A cache which keeps the lengths of small numbers in a dense array, where most lookups
+/// land, and everything else in a HashMap.
+pub struct KnownCollatzes {
+    low: Vec<u64>,
+    high: HashMap<u64, u64>
+}
+
+impl KnownCollatzes {
+    pub fn new() -> Self {
+        Self::with_dense_len(10_000_000)
+    }
+
+    /// Creates a cache whose dense array holds the lengths of 0 to dense_len - 1.
+    pub fn with_dense_len(dense_len: usize) -> Self {
+        KnownCollatzes {
+            low: vec![0; dense_len],
+            high: HashMap::new()
+        }
+    }
+
+    /// Returns the index of n in the dense array, if it has one.
+    #[inline]
+    fn low_index(&self, n: u64) -> Option<usize> {
+        usize::try_from(n).ok().filter(|&i| i < self.low.len())
+    }
+}
+
+impl CollatzCache for KnownCollatzes {
+    #[inline]
+    fn get(&self, n: u64) -> Option<u64> {
+        match self.low_index(n) {
+            // 0 marks a length which is not known yet, since every real length is at least 1.
+            Some(i) => Some(self.low[i]).filter(|&len| len != 0),
+            None => self.high.get(&n).cloned()
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, n: u64, collatz_len: u64) {
+        match self.low_index(n) {
+            Some(i) => self.low[i] = collatz_len,
+            None => {
+                self.high.insert(n, collatz_len);
+            }
+        }
+    }
+}
+
+/// A generalised Collatz map, which sends n to n / d when d divides it and to a·n + b
+/// otherwise. The usual map is `COLLATZ`, with a = 3, b = 1 and d = 2; variants such as
+/// 3n - 1 and 5n + 1 can fall into cycles which never reach 1, or grow without bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollatzMap {
+    multiplier: u64,
+    increment: i64,
+    divisor: u64
+}
+
+pub const COLLATZ: CollatzMap = CollatzMap::new(3, 1, 2);
+
+impl CollatzMap {
+    pub const fn new(multiplier: u64, increment: i64, divisor: u64) -> Self {
+        assert!(divisor >= 2, "The divisor must be at least 2");
+        CollatzMap { multiplier, increment, divisor }
+    }
+
+    /// Returns the number after n, or None if it does not fit in a u64 or is not positive.
+    /// 0 has no number after it.
+    pub fn step(&self, n: u64) -> Option<u64> {
+        if n == 0 {
+            return None;
+        }
+        if n.is_multiple_of(self.divisor) {
+            return Some(n / self.divisor);
+        }
+
+        let product = u128::from(self.multiplier).checked_mul(u128::from(n))?;
+        let next = if self.increment >= 0 {
+            product.checked_add(u128::from(self.increment.unsigned_abs()))?
+        } else {
+            product.checked_sub(u128::from(self.increment.unsigned_abs()))?
+        };
+        u64::try_from(next).ok().filter(|&next| next > 0)
+    }
+
+    /// Returns an iterator over the sequence starting at n. It ends after reaching 1, or
+    /// early if a step fails, but runs forever if the sequence falls into another cycle.
+    /// Panics if n is 0.
+    pub fn iter(&self, n: u64) -> CollatzIterator {
+        assert!(n > 0, "0 has no Collatz sequence");
+        CollatzIterator { map: *self, next: Some(n) }
+    }
+
+    /// Returns the length of the sequence starting at n, counting n and 1, or None if it
+    /// overflows a u64 or falls into a cycle which does not contain 1. Panics if n is 0.
+    pub fn len(&self, n: u64) -> Option<u64> {
+        self.stats(n).map(|stats| stats.length)
+    }
+
+    /// As `len`, but making use of and adding to a cache of known lengths, which must only
+    /// be used with this map. Nothing is added to the cache when this returns None.
+    pub fn len_cached<C: CollatzCache>(&self, n: u64, known_lengths: &mut C) -> Option<u64> {
+        assert!(n > 0, "0 has no Collatz length");
+
+        let mut n = n;
+        let mut stack = Vec::new();
+        let mut cycle = CycleDetector::new(n);
+        let clen = loop {
+            let known = if n == 1 { Some(1) } else { known_lengths.get(n) };
+            match known {
+                Some(len) => break len,
+                None => {
+                    stack.push(n);
+                    n = self.step(n)?;
+                    if cycle.seen(n) {
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let clen_for_next = stack.len() as u64 + clen;
+
+        let mut i = 1;
+        while let Some(n) = stack.pop() {
+            known_lengths.insert(n, i + clen);
+            i += 1;
+        }
+
+        Some(clen_for_next)
+    }
+
+    /// Returns the statistics of the sequence starting at n, or None if it overflows a u64
+    /// or falls into a cycle which does not contain 1.
+    pub fn stats(&self, n: u64) -> Option<CollatzStats> {
+        assert!(n > 0, "0 has no Collatz sequence");
+
+        let mut stats = CollatzStats { length: 1, max: n, glide: 0 };
+        let mut cycle = CycleDetector::new(n);
+        let mut x = n;
+        while x != 1 {
+            x = self.step(x)?;
+            stats.length += 1;
+            stats.max = stats.max.max(x);
+            if stats.glide == 0 && x < n {
+                stats.glide = stats.length - 1;
+            }
+            if cycle.seen(x) {
+                return None;
+            }
+        }
+
+        Some(stats)
+    }
+}
+
+/// Brent's cycle detection: remember the value at each power of two steps, and if the
+/// sequence comes back round to it, it is in a cycle.
+struct CycleDetector {
+    saved: u64,
+    power: u64,
+    since_saved: u64
+}
+
+impl CycleDetector {
+    fn new(start: u64) -> Self {
+        CycleDetector { saved: start, power: 1, since_saved: 0 }
+    }
+
+    /// Takes the next term of the sequence and returns whether it has been seen before.
+    #[inline]
+    fn seen(&mut self, x: u64) -> bool {
+        if x == self.saved {
+            return true;
+        }
+        self.since_saved += 1;
+        if self.since_saved == self.power {
+            self.saved = x;
+            self.power *= 2;
+            self.since_saved = 0;
+        }
+        false
+    }
+}
+
+/// An iterator over the terms of a Collatz sequence, starting with n itself. Mapping the
+/// terms to `n % 2` gives the parity vector.
+pub struct CollatzIterator {
+    map: CollatzMap,
+    next: Option<u64>
+}
+
+impl Iterator for CollatzIterator {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let n = self.next?;
+        self.next = if n == 1 { None } else { self.map.step(n) };
+        Some(n)
+    }
+}
+
+/// Returns an iterator over the Collatz sequence starting at n, ending at 1.
+pub fn collatz_sequence(n: u64) -> CollatzIterator {
+    COLLATZ.iter(n)
+}
+
+/// Statistics of a Collatz sequence which reaches 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollatzStats {
+    /// The number of terms, including the starting number and 1, as for `collatz_len`.
+    pub length: u64,
+    /// The largest term.
+    pub max: u64,
+    /// The number of steps until the sequence first drops below the starting number, also
+    /// called the stopping time. This is 0 for 1.
+    pub glide: u64
+}
+
+/// Returns the statistics of the Collatz sequence starting at n. Panics if the sequence
+/// goes above u64::MAX.
+pub fn collatz_stats(n: u64) -> CollatzStats {
+    COLLATZ.stats(n).unwrap_or_else(|| panic!("The Collatz sequence of {} overflows a u64", n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fnv::FnvHashMap;
+
+    #[test]
+    fn checked_collatz_len_simple_works() {
+        assert_eq!(checked_collatz_len_simple(1), Some(1));
+        assert_eq!(checked_collatz_len_simple(2), Some(2));
+        assert_eq!(checked_collatz_len_simple(27), Some(112));
+        assert_eq!(checked_collatz_len_simple(837_799), Some(525));
+        // 3n + 1 overflows straight away.
+        assert_eq!(checked_collatz_len_simple(u64::MAX), None);
+    }
+
+    #[test]
+    fn checked_collatz_len_works() {
+        let mut known_collatzes = HashMap::<u64, u64>::new();
+        assert_eq!(checked_collatz_len(27, &mut known_collatzes), Some(112));
+        assert_eq!(checked_collatz_len(837_799, &mut known_collatzes), Some(525));
+        assert_eq!(checked_collatz_len(u64::MAX, &mut known_collatzes), None);
+        assert_eq!(known_collatzes.get(&u64::MAX), None);
+        // Powers of two only ever halve, so they are fine however large they are.
+        assert_eq!(checked_collatz_len(1 << 63, &mut known_collatzes), Some(64));
+    }
+
+    #[test]
+    fn collatz_len_copes_with_values_beyond_u32() {
+        // 159487 goes up to 17202377752 on its way down to 1.
+        let mut known_collatzes = HashMap::<u32, u32>::new();
+        for n in 1..200_000 {
+            assert_eq!(collatz_len(n, &mut known_collatzes), collatz_len_simple(n), "collatz_len({})", n);
+        }
+    }
+
+    #[test]
+    fn every_cache_gives_the_same_lengths() {
+        let mut std_map = HashMap::<u64, u64>::new();
+        let mut fnv_map = FnvHashMap::<usize, usize>::default();
+        let mut known = KnownCollatzes::with_dense_len(1000);
+        for n in 1..50_000 {
+            let expected = collatz_len_simple(n);
+            assert_eq!(collatz_len(n, &mut std_map), expected, "HashMap collatz_len({})", n);
+            assert_eq!(collatz_len(n, &mut fnv_map), expected, "FnvHashMap collatz_len({})", n);
+            assert_eq!(collatz_len(n, &mut known), expected, "KnownCollatzes collatz_len({})", n);
+        }
+        assert_eq!(collatz_len(837_799, &mut known), 525);
+    }
+
+    #[test]
+    #[should_panic]
+    fn collatz_len_simple_panics_for_zero() {
+        collatz_len_simple(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn collatz_len_panics_for_zero() {
+        collatz_len(0, &mut HashMap::<u64, u64>::new());
+    }
+
+    #[test]
+    fn collatz_len_works() {
+        // These values were pre-computed using a dumb, non-caching collatz_len function.
+        let mut known_collatzes = HashMap::<u32, u32>::new();
+        known_collatzes.insert(1, 1);
+
+        assert_eq!(collatz_len(1, &mut known_collatzes), 1);
+        assert_eq!(collatz_len(2, &mut known_collatzes), 2);
+        assert_eq!(collatz_len(3, &mut known_collatzes), 8);
+        assert_eq!(collatz_len(4, &mut known_collatzes), 3);
+        assert_eq!(collatz_len(5, &mut known_collatzes), 6);
+        assert_eq!(collatz_len(6, &mut known_collatzes), 9);
+        assert_eq!(collatz_len(7, &mut known_collatzes), 17);
+        assert_eq!(collatz_len(8, &mut known_collatzes), 4);
+        assert_eq!(collatz_len(9, &mut known_collatzes), 20);
+
+        assert_eq!(collatz_len(10, &mut known_collatzes), 7);
+        assert_eq!(collatz_len(11, &mut known_collatzes), 15);
+        assert_eq!(collatz_len(12, &mut known_collatzes), 10);
+        assert_eq!(collatz_len(13, &mut known_collatzes), 10);
+        assert_eq!(collatz_len(14, &mut known_collatzes), 18);
+        assert_eq!(collatz_len(15, &mut known_collatzes), 18);
+        assert_eq!(collatz_len(16, &mut known_collatzes), 5);
+        assert_eq!(collatz_len(17, &mut known_collatzes), 13);
+        assert_eq!(collatz_len(18, &mut known_collatzes), 21);
+        assert_eq!(collatz_len(19, &mut known_collatzes), 21);
+
+        assert_eq!(collatz_len(20, &mut known_collatzes), 8);
+        assert_eq!(collatz_len(21, &mut known_collatzes), 8);
+        assert_eq!(collatz_len(22, &mut known_collatzes), 16);
+        assert_eq!(collatz_len(23, &mut known_collatzes), 16);
+        assert_eq!(collatz_len(24, &mut known_collatzes), 11);
+        assert_eq!(collatz_len(25, &mut known_collatzes), 24);
+        assert_eq!(collatz_len(26, &mut known_collatzes), 11);
+        assert_eq!(collatz_len(27, &mut known_collatzes), 112);
+        assert_eq!(collatz_len(28, &mut known_collatzes), 19);
+        assert_eq!(collatz_len(29, &mut known_collatzes), 19);
+
+        assert_eq!(collatz_len(30, &mut known_collatzes), 19);
+        assert_eq!(collatz_len(31, &mut known_collatzes), 107);
+        assert_eq!(collatz_len(32, &mut known_collatzes), 6);
+        assert_eq!(collatz_len(33, &mut known_collatzes), 27);
+        assert_eq!(collatz_len(34, &mut known_collatzes), 14);
+        assert_eq!(collatz_len(35, &mut known_collatzes), 14);
+        assert_eq!(collatz_len(36, &mut known_collatzes), 22);
+        assert_eq!(collatz_len(37, &mut known_collatzes), 22);
+        assert_eq!(collatz_len(38, &mut known_collatzes), 22);
+        assert_eq!(collatz_len(39, &mut known_collatzes), 35);
+
+        assert_eq!(collatz_len(40, &mut known_collatzes), 9);
+        assert_eq!(collatz_len(41, &mut known_collatzes), 110);
+        assert_eq!(collatz_len(42, &mut known_collatzes), 9);
+        assert_eq!(collatz_len(43, &mut known_collatzes), 30);
+        assert_eq!(collatz_len(44, &mut known_collatzes), 17);
+        assert_eq!(collatz_len(45, &mut known_collatzes), 17);
+        assert_eq!(collatz_len(46, &mut known_collatzes), 17);
+        assert_eq!(collatz_len(47, &mut known_collatzes), 105);
+        assert_eq!(collatz_len(48, &mut known_collatzes), 12);
+        assert_eq!(collatz_len(49, &mut known_collatzes), 25);
+    }
+
+    #[test]
+    fn collatz_sequence_works() {
+        assert_eq!(collatz_sequence(1).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(collatz_sequence(6).collect::<Vec<_>>(), vec![6, 3, 10, 5, 16, 8, 4, 2, 1]);
+        assert_eq!(collatz_sequence(27).count(), 112);
+        let parity = collatz_sequence(6).map(|n| n % 2).collect::<Vec<_>>();
+        assert_eq!(parity, vec![0, 1, 0, 1, 0, 0, 0, 0, 1]);
+        for n in 1..10_000 {
+            assert_eq!(collatz_sequence(n).count() as u64, collatz_len_simple(n), "collatz_sequence({})", n);
+        }
+        // 3n + 1 overflows straight away, so the sequence stops.
+        assert_eq!(collatz_sequence(u64::MAX).collect::<Vec<_>>(), vec![u64::MAX]);
+    }
+
+    #[test]
+    fn step_rejects_zero() {
+        assert_eq!(COLLATZ.step(0), None);
+        assert_eq!(CollatzMap::new(3, -1, 2).step(0), None);
+        assert_eq!(COLLATZ.step(1), Some(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn collatz_sequence_panics_for_zero() {
+        collatz_sequence(0);
+    }
+
+    #[test]
+    fn collatz_stats_works() {
+        assert_eq!(collatz_stats(1), CollatzStats { length: 1, max: 1, glide: 0 });
+        assert_eq!(collatz_stats(2), CollatzStats { length: 2, max: 2, glide: 1 });
+        assert_eq!(collatz_stats(7), CollatzStats { length: 17, max: 52, glide: 11 });
+        assert_eq!(collatz_stats(27), CollatzStats { length: 112, max: 9232, glide: 96 });
+        assert_eq!(collatz_stats(77_671).max, 1_570_824_736);
+
+        // OEIS A102419, from n = 2.
+        let glides = (2..14).map(|n| collatz_stats(n).glide).collect::<Vec<_>>();
+        assert_eq!(glides, vec![1, 6, 1, 3, 1, 11, 1, 3, 1, 8, 1, 3]);
+
+        for n in 1..10_000 {
+            let stats = collatz_stats(n);
+            assert_eq!(stats.length, collatz_len_simple(n));
+            assert_eq!(stats.max, collatz_sequence(n).max().unwrap());
+        }
+        assert_eq!(COLLATZ.stats(u64::MAX), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn collatz_stats_panics_on_overflow() {
+        collatz_stats(u64::MAX);
+    }
+
+    #[test]
+    fn generalised_lengths_work() {
+        let minus_one = CollatzMap::new(3, -1, 2);
+        let mut known_lengths = HashMap::<u64, u64>::new();
+        assert_eq!(minus_one.len(3), Some(5));
+        assert_eq!(minus_one.len_cached(3, &mut known_lengths), Some(5));
+        assert_eq!(minus_one.len_cached(5, &mut known_lengths), None);
+        assert_eq!(known_lengths.get(&5), None);
+        for n in 1..10_000 {
+            assert_eq!(minus_one.len_cached(n, &mut known_lengths), minus_one.len(n), "len_cached({})", n);
+        }
+
+        let five = CollatzMap::new(5, 1, 2);
+        assert_eq!(five.len_cached(3, &mut HashMap::<u64, u64>::new()), Some(6));
+        assert_eq!(five.len_cached(7, &mut HashMap::<u64, u64>::new()), None);
+        assert_eq!(COLLATZ.len(27), Some(112));
+    }
+
+    #[test]
+    fn generalised_maps_work() {
+        let minus_one = CollatzMap::new(3, -1, 2);
+        assert_eq!(minus_one.iter(3).collect::<Vec<_>>(), vec![3, 8, 4, 2, 1]);
+        assert_eq!(minus_one.stats(3), Some(CollatzStats { length: 5, max: 8, glide: 3 }));
+        // 5 and 17 fall into the two cycles which do not contain 1.
+        assert_eq!(minus_one.iter(5).take(6).collect::<Vec<_>>(), vec![5, 14, 7, 20, 10, 5]);
+        assert_eq!(minus_one.stats(5), None);
+        assert_eq!(minus_one.stats(7), None);
+        assert_eq!(minus_one.stats(17), None);
+
+        let five = CollatzMap::new(5, 1, 2);
+        assert_eq!(five.stats(3), Some(CollatzStats { length: 6, max: 16, glide: 4 }));
+        assert_eq!(five.iter(13).take(5).collect::<Vec<_>>(), vec![13, 66, 33, 166, 83]);
+        assert_eq!(five.stats(13), None);
+        // 7 appears to grow without bound, so it eventually overflows.
+        assert_eq!(five.stats(7), None);
+
+        let thirds = CollatzMap::new(1, 1, 3);
+        assert_eq!(thirds.iter(7).collect::<Vec<_>>(), vec![7, 8, 9, 3, 1]);
+        assert_eq!(COLLATZ.step(27), Some(82));
+        assert_eq!(CollatzMap::new(1, -1, 2).step(1), None);
+    }
+}
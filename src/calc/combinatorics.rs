@@ -0,0 +1,344 @@
+use calc::modint::{DynamicModulus, ModInt};
+
+// Factorials, binomial coefficients, permutations and combinations. Following the overflow
+// policy in `overflow`, the plain functions panic if the result does not fit in a u64 and
+// the `checked_*` variants return None.
+
+/// Returns n!. Panics if it does not fit in a u64, which is from 21! onwards.
+pub fn factorial(n: u64) -> u64 {
+    checked_factorial(n).unwrap_or_else(|| panic!("{}! overflows a u64", n))
+}
+
+/// Returns n!, or None if it does not fit in a u64.
+pub fn checked_factorial(n: u64) -> Option<u64> {
+    (2..=n).try_fold(1_u64, |f, i| f.checked_mul(i))
+}
+
+/// Returns the binomial coefficient C(n, k), the number of ways of choosing k things from n.
+/// This is 0 if k > n. Panics if the result does not fit in a u64.
+pub fn binomial(n: u64, k: u64) -> u64 {
+    checked_binomial(n, k).unwrap_or_else(|| panic!("C({}, {}) overflows a u64", n, k))
+}
+
+/// Returns the binomial coefficient C(n, k), or None if it does not fit in a u64.
+pub fn checked_binomial(n: u64, k: u64) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+
+    // After step i the result is C(n - k + i, i), which is always a whole number and never
+    // more than the final answer, so the intermediate product fits in a u128 whenever the
+    // answer fits in a u64.
+    let k = k.min(n - k);
+    let mut result = 1_u128;
+    for i in 1..k + 1 {
+        result = result * u128::from(n - k + i) / u128::from(i);
+        if result > u128::from(u64::MAX) {
+            return None;
+        }
+    }
+    Some(result as u64)
+}
+
+/// Returns C(n, k) mod p, for a prime p, using Lucas' theorem: C(n, k) is congruent to the
+/// product of C(n_i, k_i) over the base p digits of n and k. This works for any n and k,
+/// however large C(n, k) itself is.
+pub fn binomial_mod(mut n: u64, mut k: u64, p: u64) -> u64 {
+    assert!(p > 1, "The modulus must be a prime");
+    let modulus = DynamicModulus::new(p);
+    let mut result = ModInt::with_modulus(1, modulus);
+    while k > 0 {
+        let (ni, ki) = (n % p, k % p);
+        if ki > ni {
+            return 0;
+        }
+
+        // C(ni, ki) = ni! / (ki! (ni - ki)!), and none of the factors are divisible by p.
+        let ki = ki.min(ni - ki);
+        let mut numerator = ModInt::with_modulus(1, modulus);
+        let mut denominator = ModInt::with_modulus(1, modulus);
+        for i in 1..ki + 1 {
+            numerator *= ModInt::with_modulus(ni - ki + i, modulus);
+            denominator *= ModInt::with_modulus(i, modulus);
+        }
+        result *= numerator / denominator;
+
+        n /= p;
+        k /= p;
+    }
+    result.value()
+}
+
+/// Returns the first `rows` rows of Pascal's triangle, so that `table[n][k]` is C(n, k).
+/// Handy when many binomial coefficients are needed. Panics if an entry does not fit in a
+/// u64, so at most 68 rows can be made.
+pub fn pascal_triangle(rows: usize) -> Vec<Vec<u64>> {
+    let mut table: Vec<Vec<u64>> = Vec::with_capacity(rows);
+    for n in 0..rows {
+        let mut row = vec![1; n + 1];
+        for k in 1..n {
+            row[k] = table[n - 1][k - 1].checked_add(table[n - 1][k])
+                .unwrap_or_else(|| panic!("C({}, {}) overflows a u64", n, k));
+        }
+        table.push(row);
+    }
+    table
+}
+
+/// Returns the n'th permutation of the items in lexicographic order, counting from 0 and
+/// treating the items as already sorted. Returns None if there are fewer than n + 1
+/// permutations. Works by writing n in the factorial number system, where each digit picks
+/// one of the remaining items.
+pub fn nth_permutation<T: Clone>(items: &[T], mut n: u64) -> Option<Vec<T>> {
+    if checked_factorial(items.len() as u64).is_some_and(|count| n >= count) {
+        return None;
+    }
+
+    let mut remaining = items.to_vec();
+    let mut permutation = Vec::with_capacity(items.len());
+    for i in (0..items.len() as u64).rev() {
+        // With more than 21 items i! does not fit in a u64, but then n < i! so the
+        // digit is 0.
+        let digit = match checked_factorial(i) {
+            Some(block) => {
+                let digit = n / block;
+                n %= block;
+                digit
+            }
+            None => 0
+        };
+        permutation.push(remaining.remove(digit as usize));
+    }
+    Some(permutation)
+}
+
+/// Rearranges the items into the next permutation in lexicographic order and returns true,
+/// or returns false and leaves them alone if they are already the last one. Repeated items
+/// are handled, so each distinct arrangement is produced once.
+pub fn next_permutation<T: Ord>(items: &mut [T]) -> bool {
+    let i = match items.windows(2).rposition(|w| w[0] < w[1]) {
+        Some(i) => i,
+        None => return false
+    };
+    let j = items.iter().rposition(|x| *x > items[i]).unwrap();
+    items.swap(i, j);
+    items[i + 1..].reverse();
+    true
+}
+
+/// Rearranges the items into the previous permutation in lexicographic order and returns
+/// true, or returns false and leaves them alone if they are already the first one.
+pub fn prev_permutation<T: Ord>(items: &mut [T]) -> bool {
+    let i = match items.windows(2).rposition(|w| w[0] > w[1]) {
+        Some(i) => i,
+        None => return false
+    };
+    let j = items.iter().rposition(|x| *x < items[i]).unwrap();
+    items.swap(i, j);
+    items[i + 1..].reverse();
+    true
+}
+
+/// An iterator over the k-subsets of a slice, in lexicographic order of position.
+pub struct Combinations<'a, T: 'a> {
+    items: &'a [T],
+    indices: Vec<usize>,
+    done: bool
+}
+
+/// Returns an iterator over the ways of choosing k of the items, preserving their order.
+/// There are C(items.len(), k) of them.
+pub fn combinations<T: Clone>(items: &[T], k: usize) -> Combinations<'_, T> {
+    Combinations { items, indices: (0..k).collect(), done: k > items.len() }
+}
+
+impl<'a, T: Clone> Iterator for Combinations<'a, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+
+        let combination = self.indices.iter().map(|&i| self.items[i].clone()).collect();
+
+        // Advance the rightmost index which still has room to move, then reset the ones
+        // after it to follow on from it.
+        let n = self.items.len();
+        let k = self.indices.len();
+        match (0..k).rposition(|i| self.indices[i] < n - k + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true
+        }
+
+        Some(combination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorial_works() {
+        assert_eq!(factorial(0), 1);
+        assert_eq!(factorial(1), 1);
+        assert_eq!(factorial(10), 3_628_800);
+        assert_eq!(factorial(20), 2_432_902_008_176_640_000);
+        assert_eq!(checked_factorial(20), Some(2_432_902_008_176_640_000));
+        assert_eq!(checked_factorial(21), None);
+        assert_eq!(checked_factorial(u64::MAX), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn factorial_panics_on_overflow() {
+        factorial(21);
+    }
+
+    #[test]
+    fn binomial_works() {
+        assert_eq!(binomial(0, 0), 1);
+        assert_eq!(binomial(5, 0), 1);
+        assert_eq!(binomial(5, 2), 10);
+        assert_eq!(binomial(5, 5), 1);
+        assert_eq!(binomial(5, 6), 0);
+        assert_eq!(binomial(52, 5), 2_598_960);
+        assert_eq!(binomial(u64::MAX, 1), u64::MAX);
+        assert_eq!(binomial(u64::MAX, u64::MAX - 1), u64::MAX);
+        // The largest central coefficient which fits in a u64.
+        assert_eq!(checked_binomial(66, 33), Some(7_219_428_434_016_265_740));
+        assert_eq!(checked_binomial(68, 34), None);
+        assert_eq!(checked_binomial(u64::MAX, 2), None);
+    }
+
+    #[test]
+    fn binomial_matches_pascal_triangle() {
+        let table = pascal_triangle(68);
+        for (n, row) in table.iter().enumerate() {
+            assert_eq!(row.len(), n + 1);
+            for (k, &c) in row.iter().enumerate() {
+                assert_eq!(binomial(n as u64, k as u64), c, "C({}, {})", n, k);
+            }
+        }
+        assert!(pascal_triangle(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn pascal_triangle_panics_on_overflow() {
+        pascal_triangle(69);
+    }
+
+    #[test]
+    fn binomial_mod_works() {
+        let table = pascal_triangle(60);
+        for &p in &[2, 3, 5, 7, 13, 1_000_000_007] {
+            for n in 0..60 {
+                for k in 0..n + 1 {
+                    assert_eq!(binomial_mod(n, k, p), table[n as usize][k as usize] % p, "C({}, {}) mod {}", n, k, p);
+                }
+            }
+        }
+        assert_eq!(binomial_mod(10, 11, 7), 0);
+        // C(1000, 500) mod 1e9+7, and a case where Lucas splits n and k into several digits.
+        assert_eq!(binomial_mod(1000, 500, 1_000_000_007), 159_835_829);
+        assert_eq!(binomial_mod(1_000_000_000_000, 123_456_789, 13), {
+            let (mut n, mut k, mut result) = (1_000_000_000_000, 123_456_789, 1);
+            while k > 0 {
+                result = result * binomial(n % 13, k % 13) % 13;
+                n /= 13;
+                k /= 13;
+            }
+            result
+        });
+    }
+
+    #[test]
+    fn nth_permutation_works() {
+        let items = [0, 1, 2];
+        let all = (0..7).map(|n| nth_permutation(&items, n)).collect::<Vec<_>>();
+        assert_eq!(all, vec![
+            Some(vec![0, 1, 2]), Some(vec![0, 2, 1]), Some(vec![1, 0, 2]),
+            Some(vec![1, 2, 0]), Some(vec![2, 0, 1]), Some(vec![2, 1, 0]), None
+        ]);
+        assert_eq!(nth_permutation::<u8>(&[], 0), Some(vec![]));
+        assert_eq!(nth_permutation::<u8>(&[], 1), None);
+
+        let many = (0..30).collect::<Vec<_>>();
+        let last = nth_permutation(&many, u64::MAX).unwrap();
+        assert_eq!(&last[..9], &many[..9]);
+        assert_ne!(last[9], many[9]);
+    }
+
+    #[test]
+    fn next_permutation_matches_nth_permutation() {
+        let mut items = vec!['a', 'b', 'c', 'd', 'e'];
+        let mut n = 0;
+        loop {
+            assert_eq!(Some(items.clone()), nth_permutation(&['a', 'b', 'c', 'd', 'e'], n));
+            n += 1;
+            if !next_permutation(&mut items) {
+                break;
+            }
+        }
+        assert_eq!(n, 120);
+        assert_eq!(items, vec!['e', 'd', 'c', 'b', 'a']);
+
+        while prev_permutation(&mut items) {
+            n -= 1;
+        }
+        assert_eq!(n, 1);
+        assert_eq!(items, vec!['a', 'b', 'c', 'd', 'e']);
+    }
+
+    #[test]
+    fn next_permutation_handles_repeats() {
+        let mut items = [1, 1, 2];
+        let mut all = vec![items];
+        while next_permutation(&mut items) {
+            all.push(items);
+        }
+        assert_eq!(all, vec![[1, 1, 2], [1, 2, 1], [2, 1, 1]]);
+    }
+
+    #[test]
+    fn combinations_works() {
+        let c = combinations(&[1, 2, 3, 4], 2).collect::<Vec<_>>();
+        assert_eq!(c, vec![vec![1, 2], vec![1, 3], vec![1, 4], vec![2, 3], vec![2, 4], vec![3, 4]]);
+        assert_eq!(combinations(&[1, 2, 3], 0).collect::<Vec<_>>(), vec![Vec::<i32>::new()]);
+        assert_eq!(combinations(&[1, 2, 3], 3).collect::<Vec<_>>(), vec![vec![1, 2, 3]]);
+        assert_eq!(combinations(&[1, 2, 3], 4).count(), 0);
+        for k in 0..11 {
+            assert_eq!(combinations(&[0; 10], k).count() as u64, binomial(10, k as u64));
+        }
+    }
+
+    #[test]
+    fn solves_problem_15() {
+        // The number of lattice paths through a 20 x 20 grid.
+        assert_eq!(binomial(40, 20), 137_846_528_820);
+    }
+
+    #[test]
+    fn solves_problem_24() {
+        // The millionth lexicographic permutation of the digits 0 to 9.
+        let digits = nth_permutation(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9], 999_999).unwrap();
+        assert_eq!(digits.iter().fold(0_u64, |n, &d| n * 10 + d), 2_783_915_460);
+    }
+
+    #[test]
+    fn solves_problem_53() {
+        // How many C(n, r) for n up to 100 are greater than one million.
+        let answer = (1..101)
+            .flat_map(|n| (0..n + 1).map(move |r| (n, r)))
+            .filter(|&(n, r)| checked_binomial(n, r).is_none_or(|c| c > 1_000_000))
+            .count();
+        assert_eq!(answer, 4075);
+    }
+}
@@ -0,0 +1,290 @@
+use std::convert::TryFrom;
+use calc::roots::isqrt;
+
+// Polygonal (figurate) numbers. The n'th s-gonal number is
+//
+//     P(s, n) = ((s - 2)n^2 - (s - 4)n) / 2
+//
+// which gives the triangle numbers for s = 3, the squares for s = 4 and so on. Solving
+// that quadratic for n gives the inverse, which tells us whether a number is s-gonal.
+
+/// A family of polygonal numbers, identified by the number of sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Polygonal {
+    sides: u32
+}
+
+pub const TRIANGLE: Polygonal = Polygonal::new(3);
+pub const SQUARE: Polygonal = Polygonal::new(4);
+pub const PENTAGONAL: Polygonal = Polygonal::new(5);
+pub const HEXAGONAL: Polygonal = Polygonal::new(6);
+pub const HEPTAGONAL: Polygonal = Polygonal::new(7);
+pub const OCTAGONAL: Polygonal = Polygonal::new(8);
+
+impl Polygonal {
+    pub const fn new(sides: u32) -> Self {
+        assert!(sides >= 3, "A polygon needs at least 3 sides");
+        Polygonal { sides }
+    }
+
+    #[inline]
+    pub fn sides(&self) -> u32 {
+        self.sides
+    }
+
+    /// Returns the n'th number. Panics if it does not fit in a u64.
+    pub fn nth(&self, n: u64) -> u64 {
+        self.checked_nth(n).unwrap_or_else(|| panic!("The {}-gonal number {} overflows a u64", self.sides, n))
+    }
+
+    /// Returns the n'th number, or None if it does not fit in a u64.
+    pub fn checked_nth(&self, n: u64) -> Option<u64> {
+        // (s - 2)n^2 + 4n >= sn, so this never goes negative, but near the top of the range
+        // the sum can overflow even a u128.
+        let s = u128::from(self.sides);
+        let n = u128::from(n);
+        let value = (s - 2).checked_mul(n)?.checked_mul(n)?
+            .checked_add(4 * n)?
+            .checked_sub(s.checked_mul(n)?)? / 2;
+        u64::try_from(value).ok()
+    }
+
+    /// Returns n such that x is the n'th number, or None if x is not one of these numbers.
+    /// 0 is number 0.
+    pub fn index_of(&self, x: u64) -> Option<u64> {
+        if x == 0 {
+            return Some(0);
+        }
+
+        // n = ((s - 4) + sqrt((s - 4)^2 + 8(s - 2)x)) / 2(s - 2).
+        let s = i128::from(self.sides);
+        let disc = ((s - 4) * (s - 4)) as u128 + 8 * (s - 2) as u128 * u128::from(x);
+        let r = isqrt(disc);
+        if r * r != disc {
+            return None;
+        }
+
+        let numerator = (s - 4) + r as i128;
+        let denominator = 2 * (s - 2);
+        if numerator % denominator == 0 { Some((numerator / denominator) as u64) } else { None }
+    }
+
+    /// Returns true if x is one of these numbers.
+    #[inline]
+    pub fn contains(&self, x: u64) -> bool {
+        self.index_of(x).is_some()
+    }
+
+    /// Returns an iterator over the numbers, starting at n = 1. It ends at the last number
+    /// which fits in a u64.
+    pub fn iter(&self) -> PolygonalIterator {
+        PolygonalIterator { polygonal: *self, n: 1 }
+    }
+
+    /// Returns the generalised number for any integer k, which is P(s, k) with negative k
+    /// allowed. For example the generalised pentagonal numbers, used in Euler's pentagonal
+    /// number theorem, are P(5, k) for k = 0, 1, -1, 2, -2, ... Panics if it does not fit in
+    /// a u64.
+    pub fn nth_generalised(&self, k: i64) -> u64 {
+        self.checked_nth_generalised(k)
+            .unwrap_or_else(|| panic!("The generalised {}-gonal number {} overflows a u64", self.sides, k))
+    }
+
+    /// Returns the generalised number for k, or None if it does not fit in a u64.
+    pub fn checked_nth_generalised(&self, k: i64) -> Option<u64> {
+        // k^2 < 2^126, and (s - 4)k is well within an i128, so only the products involving
+        // s - 2 and the final subtraction can overflow.
+        let s = i128::from(self.sides);
+        let k = i128::from(k);
+        let value = (s - 2).checked_mul(k * k)?.checked_sub((s - 4) * k)? / 2;
+        u64::try_from(value).ok()
+    }
+
+    /// Returns an iterator over the generalised numbers for k = 0, 1, -1, 2, -2, ...
+    /// For 5 or more sides these come out in ascending order. The generalised triangle
+    /// numbers are the triangle numbers, each repeated. It ends before the first number
+    /// which does not fit in a u64.
+    pub fn generalised(&self) -> impl Iterator<Item = u64> {
+        let polygonal = *self;
+        (0..).flat_map(|k: i64| if k == 0 { vec![0] } else { vec![k, -k] })
+            .map_while(move |k| polygonal.checked_nth_generalised(k))
+    }
+}
+
+/// An iterator over the numbers in a family of polygonal numbers.
+pub struct PolygonalIterator {
+    polygonal: Polygonal,
+    n: u64
+}
+
+impl Iterator for PolygonalIterator {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let value = self.polygonal.checked_nth(self.n)?;
+        self.n += 1;
+        Some(value)
+    }
+}
+
+pub fn is_triangle(x: u64) -> bool {
+    TRIANGLE.contains(x)
+}
+
+pub fn is_square(x: u64) -> bool {
+    SQUARE.contains(x)
+}
+
+pub fn is_pentagonal(x: u64) -> bool {
+    PENTAGONAL.contains(x)
+}
+
+pub fn is_hexagonal(x: u64) -> bool {
+    HEXAGONAL.contains(x)
+}
+
+pub fn is_heptagonal(x: u64) -> bool {
+    HEPTAGONAL.contains(x)
+}
+
+pub fn is_octagonal(x: u64) -> bool {
+    OCTAGONAL.contains(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nth_works() {
+        // OEIS A000217, A000290, A000326, A000384, A000566 and A000567.
+        let first = |p: Polygonal| p.iter().take(6).collect::<Vec<_>>();
+        assert_eq!(first(TRIANGLE), vec![1, 3, 6, 10, 15, 21]);
+        assert_eq!(first(SQUARE), vec![1, 4, 9, 16, 25, 36]);
+        assert_eq!(first(PENTAGONAL), vec![1, 5, 12, 22, 35, 51]);
+        assert_eq!(first(HEXAGONAL), vec![1, 6, 15, 28, 45, 66]);
+        assert_eq!(first(HEPTAGONAL), vec![1, 7, 18, 34, 55, 81]);
+        assert_eq!(first(OCTAGONAL), vec![1, 8, 21, 40, 65, 96]);
+        assert_eq!(Polygonal::new(10).nth(4), 52);
+        assert_eq!(TRIANGLE.nth(0), 0);
+        assert_eq!(TRIANGLE.nth(500), 125_250);
+    }
+
+    #[test]
+    fn nth_near_the_limit_works() {
+        // The largest triangle number which fits in a u64 is number 6074000999.
+        assert_eq!(TRIANGLE.checked_nth(6_074_000_999), Some(18_446_744_070_963_499_500));
+        assert_eq!(TRIANGLE.checked_nth(6_074_001_000), None);
+        assert_eq!(SQUARE.checked_nth(u64::from(u32::MAX)), Some(u64::from(u32::MAX) * u64::from(u32::MAX)));
+        assert_eq!(SQUARE.checked_nth(1 << 32), None);
+        // (s - 2)n^2 fits in a u128 here, but adding 4n used to overflow it.
+        assert_eq!(SQUARE.checked_nth(13_043_817_825_332_782_212), None);
+        assert_eq!(SQUARE.checked_nth(u64::MAX), None);
+        assert_eq!(Polygonal::new(u32::MAX).checked_nth(92_682), Some(18_446_584_824_912_354_435));
+        assert_eq!(Polygonal::new(u32::MAX).checked_nth(92_683), None);
+        assert_eq!(PENTAGONAL.checked_nth(3_506_826_112), Some(18_446_744_067_954_141_760));
+        assert_eq!(PENTAGONAL.checked_nth(3_506_826_113), None);
+        assert_eq!(HEXAGONAL.checked_nth(3_037_000_500), Some(18_446_744_070_963_499_500));
+        assert_eq!(HEXAGONAL.checked_nth(3_037_000_501), None);
+        assert_eq!(OCTAGONAL.checked_nth(2_479_700_524), Some(18_446_744_061_218_222_680));
+        assert_eq!(OCTAGONAL.checked_nth(2_479_700_525), None);
+        assert_eq!(PolygonalIterator { polygonal: TRIANGLE, n: 6_074_000_990 }.count(), 10);
+        assert_eq!(Polygonal::new(u32::MAX).checked_nth(u64::MAX), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nth_panics_on_overflow() {
+        TRIANGLE.nth(1 << 40);
+    }
+
+    #[test]
+    fn index_of_is_the_inverse_of_nth() {
+        for sides in 3..20 {
+            let p = Polygonal::new(sides);
+            let values = p.iter().take(200).collect::<Vec<_>>();
+            for x in 0..values[199] {
+                let expected = if x == 0 { Some(0) } else { values.binary_search(&x).ok().map(|i| i as u64 + 1) };
+                assert_eq!(p.index_of(x), expected, "{}-gonal index_of({})", sides, x);
+            }
+        }
+
+        assert_eq!(TRIANGLE.index_of(18_446_744_070_963_499_500), Some(6_074_000_999));
+        assert_eq!(TRIANGLE.index_of(18_446_744_070_963_499_501), None);
+        assert_eq!(SQUARE.index_of(u64::MAX), None);
+        assert_eq!(OCTAGONAL.index_of(OCTAGONAL.nth(1_000_000)), Some(1_000_000));
+    }
+
+    #[test]
+    fn is_functions_work() {
+        assert!(is_triangle(28) && !is_triangle(29));
+        assert!(is_square(49) && !is_square(50));
+        assert!(is_pentagonal(22) && !is_pentagonal(23));
+        assert!(is_hexagonal(28) && !is_hexagonal(21));
+        assert!(is_heptagonal(34) && !is_heptagonal(35));
+        assert!(is_octagonal(40) && !is_octagonal(41));
+    }
+
+    #[test]
+    fn generalised_works() {
+        // OEIS A001318.
+        let g = PENTAGONAL.generalised().take(10).collect::<Vec<_>>();
+        assert_eq!(g, vec![0, 1, 2, 5, 7, 12, 15, 22, 26, 35]);
+        // OEIS A000217 doubled up.
+        let g = TRIANGLE.generalised().take(7).collect::<Vec<_>>();
+        assert_eq!(g, vec![0, 1, 0, 3, 1, 6, 3]);
+        assert_eq!(HEXAGONAL.nth_generalised(-2), 10);
+    }
+
+    #[test]
+    fn generalised_near_the_limit_works() {
+        // For k = 3506826112 both P(5, k) and P(5, -k) fit, but neither does for the next k.
+        assert_eq!(PENTAGONAL.checked_nth_generalised(3_506_826_112), Some(18_446_744_067_954_141_760));
+        assert_eq!(PENTAGONAL.checked_nth_generalised(-3_506_826_112), Some(18_446_744_071_460_967_872));
+        assert_eq!(PENTAGONAL.checked_nth_generalised(3_506_826_113), None);
+        assert_eq!(PENTAGONAL.checked_nth_generalised(i64::MIN), None);
+        assert_eq!(TRIANGLE.checked_nth_generalised(-6_074_001_000), Some(18_446_744_070_963_499_500));
+        assert_eq!(TRIANGLE.checked_nth_generalised(6_074_001_000), None);
+        assert_eq!(Polygonal::new(u32::MAX).checked_nth_generalised(i64::MAX), None);
+
+        // With this many sides the iterator runs out after k = 92681, as P(s, -92682) is
+        // the first number which does not fit.
+        let big = Polygonal::new(u32::MAX);
+        let mut count = 0;
+        let mut last = 0;
+        for g in big.generalised() {
+            count += 1;
+            last = g;
+        }
+        assert_eq!(count, 185_364);
+        assert_eq!(last, 18_446_584_824_912_354_435);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nth_generalised_panics_on_overflow() {
+        PENTAGONAL.nth_generalised(3_506_826_113);
+    }
+
+    #[test]
+    fn solves_problem_44() {
+        // The pair of pentagonal numbers whose sum and difference are both pentagonal,
+        // with the smallest difference.
+        let answer = PENTAGONAL.iter().enumerate()
+            .filter_map(|(k, pk)| {
+                PENTAGONAL.iter().take(k).find(|&pj| is_pentagonal(pk - pj) && is_pentagonal(pk + pj)).map(|pj| pk - pj)
+            })
+            .next()
+            .unwrap();
+        assert_eq!(answer, 5_482_660);
+    }
+
+    #[test]
+    fn solves_problem_45() {
+        // Every hexagonal number is triangular, so find the next hexagonal number after
+        // 40755 which is also pentagonal.
+        let answer = HEXAGONAL.iter().find(|&h| h > 40_755 && is_pentagonal(h)).unwrap();
+        assert!(is_triangle(answer));
+        assert_eq!(answer, 1_533_776_805);
+    }
+}
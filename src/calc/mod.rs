@@ -0,0 +1,292 @@
+use prime::cache::PRIMES;
+use prime::factorize_with;
+use digits;
+
+pub mod aliquot;
+pub mod collatz;
+pub mod combinatorics;
+pub mod figurate;
+pub mod modint;
+pub mod number_theory;
+pub mod partitions;
+pub mod roots;
+
+// The binary itself only needs some of these, but calc::gcd is a long-standing path.
+#[allow(unused_imports)]
+pub use self::number_theory::{gcd, lcm, checked_lcm};
+
+/// Compute ceil(sqrt(n)). Note that this cannot be used in a simple range for things such
+/// as prime number sieves because it will fail if ceil(sqrt(n)) == sqrt(n). You need an upper
+/// bound which is one more than this. Try `sqrt_upper_bound` instead.
+pub fn sqrt_ceil(n: u64) -> u64 {
+    let r = roots::isqrt(n);
+    if r * r == n { r } else { r + 1 }
+}
+
+/// Compute floor(sqrt(n)) + 1. This can be used as an exclusive upper bound in
+/// loop ranges for things such as computing prime numbers.
+pub fn sqrt_upper_bound(n: u64) -> u64 {
+    roots::isqrt(n) + 1
+}
+
+/// Returns the n'th triangle number. See `figurate` for the other polygonal numbers.
+/// Panics if the result does not fit in a u64.
+pub fn triangle(n: u64) -> u64 {
+    figurate::TRIANGLE.nth(n)
+}
+
+/// Returns the n'th triangle number, or None if it does not fit in a u64.
+pub fn checked_triangle(n: u64) -> Option<u64> {
+    figurate::TRIANGLE.checked_nth(n)
+}
+
+/// Returns a sorted vector of the divisors of n, including 1 and n itself.
+pub fn divisors(n: u64) -> Vec<u64> {
+    // Divisors come in pairs (d, n / d) with d <= sqrt(n), so collect the small halves in
+    // ascending order and the large halves in descending order, then join them up.
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+
+    let mut d = 1;
+    while d <= n / d {
+        if n.is_multiple_of(d) {
+            small.push(d);
+            if d != n / d {
+                large.push(n / d);
+            }
+        }
+        d += 1;
+    }
+
+    small.extend(large.into_iter().rev());
+    small
+}
+
+/// Returns an iterator over the divisors of n in no particular order. This avoids the
+/// allocation and sorting done by `divisors`, which is all wasted if you are just going to
+/// sum or count them.
+pub fn divisors_unsorted(n: u64) -> Divisors {
+    Divisors { n, d: 0, paired: None }
+}
+
+/// Returns a sorted vector of the proper divisors of n, which are all the divisors except n.
+pub fn proper_divisors(n: u64) -> Vec<u64> {
+    let mut result = divisors(n);
+    result.pop();
+    result
+}
+
+/// Returns the sum of the divisors of n, σ(n), including n itself.
+/// This is computed from the prime factorisation, using primes from the global cache.
+pub fn sum_of_divisors(n: u64) -> u64 {
+    assert!(n > 0, "0 has infinitely many divisors");
+    factorize_with(n, PRIMES.iter()).sum_of_divisors()
+}
+
+/// Returns the sum of the proper divisors of n, also known as the aliquot sum.
+pub fn sum_of_proper_divisors(n: u64) -> u64 {
+    sum_of_divisors(n) - n
+}
+
+/// An iterator over the divisors of a number. See `divisors_unsorted`.
+pub struct Divisors {
+    n: u64,
+    d: u64,
+    // The larger divisor n / d, waiting to be returned after d.
+    paired: Option<u64>
+}
+
+impl Iterator for Divisors {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if let Some(paired) = self.paired.take() {
+            return Some(paired);
+        }
+
+        loop {
+            self.d += 1;
+            if self.d > self.n / self.d {
+                return None;
+            }
+
+            if self.n.is_multiple_of(self.d) {
+                let paired = self.n / self.d;
+                if paired != self.d {
+                    self.paired = Some(paired);
+                }
+                return Some(self.d);
+            }
+        }
+    }
+}
+
+/// Returns the number of divisors of n, τ(n), including 1 and n itself.
+/// This is computed from the prime factorisation, using primes from the global cache.
+pub fn num_divisors(n: u64) -> u64 {
+    assert!(n > 0, "0 has infinitely many divisors");
+    factorize_with(n, PRIMES.iter()).num_divisors()
+}
+
+/// Given a vector of digits, convert it to a number. See `digits::from_digits` for other
+/// bases.
+///
+///     assert_eq!(vec_to_num(vec![1, 2, 3, 0]), 1230);
+///
+pub fn vec_to_num(v: &[u64]) -> u64 {
+    digits::from_digits(v.iter().map(|&d| {
+        assert!(d < 10, "{} is not a decimal digit", d);
+        d as u32
+    }), 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_ceil_works() {
+        assert_eq!(sqrt_ceil(1), 1);
+        assert_eq!(sqrt_ceil(2), 2);
+        assert_eq!(sqrt_ceil(3), 2);
+        assert_eq!(sqrt_ceil(4), 2);
+        assert_eq!(sqrt_ceil(5), 3);
+        assert_eq!(sqrt_ceil(6), 3);
+        assert_eq!(sqrt_ceil(7), 3);
+        assert_eq!(sqrt_ceil(8), 3);
+        assert_eq!(sqrt_ceil(9), 3);
+        assert_eq!(sqrt_ceil(10), 4);
+    }
+
+    #[test]
+    fn sqrt_upper_bound_works() {
+        assert_eq!(sqrt_upper_bound(1), 2);
+        assert_eq!(sqrt_upper_bound(2), 2);
+        assert_eq!(sqrt_upper_bound(3), 2);
+        assert_eq!(sqrt_upper_bound(4), 3);
+        assert_eq!(sqrt_upper_bound(5), 3);
+        assert_eq!(sqrt_upper_bound(6), 3);
+        assert_eq!(sqrt_upper_bound(7), 3);
+        assert_eq!(sqrt_upper_bound(8), 3);
+        assert_eq!(sqrt_upper_bound(9), 4);
+        assert_eq!(sqrt_upper_bound(10), 4);
+    }
+
+    #[test]
+    fn sqrt_helpers_are_exact_for_large_n() {
+        // (2^32 - 1)^2 and its neighbours, where going through f64 rounds up to 2^32.
+        let r = u64::from(u32::MAX);
+        assert_eq!(sqrt_ceil(r * r), r);
+        assert_eq!(sqrt_ceil(r * r - 1), r);
+        assert_eq!(sqrt_ceil(r * r + 1), r + 1);
+        assert_eq!(sqrt_upper_bound(r * r - 1), r);
+        assert_eq!(sqrt_upper_bound(r * r), r + 1);
+        assert_eq!(sqrt_upper_bound(u64::MAX), r + 1);
+    }
+    
+    #[test]
+    fn triangle_works() {
+        assert_eq!(triangle(1), 1);
+        assert_eq!(triangle(2), 3);
+        assert_eq!(triangle(3), 6);
+        assert_eq!(triangle(4), 10);
+        assert_eq!(triangle(5), 15);
+        assert_eq!(triangle(6), 21);
+        assert_eq!(triangle(7), 28);
+        assert_eq!(triangle(500), 125250);
+    }
+
+    #[test]
+    fn divisors_works() {
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(2), vec![1, 2]);
+        assert_eq!(divisors(3), vec![1, 3]);
+        assert_eq!(divisors(4), vec![1, 2, 4]);
+        assert_eq!(divisors(5), vec![1, 5]);
+        assert_eq!(divisors(6), vec![1, 2, 3, 6]);
+        assert_eq!(divisors(7), vec![1, 7]);
+        assert_eq!(divisors(8), vec![1, 2, 4, 8]);
+        assert_eq!(divisors(9), vec![1, 3, 9]);
+        assert_eq!(divisors(10), vec![1, 2, 5, 10]);
+        assert_eq!(divisors(28), vec![1, 2, 4, 7, 14, 28]);
+        assert_eq!(divisors(76_576_500).len(), 576);
+    }
+
+    #[test]
+    fn divisors_matches_brute_force() {
+        for n in 1..2001 {
+            let expected = (1..n + 1).filter(|d| n % d == 0).collect::<Vec<_>>();
+            assert_eq!(divisors(n), expected, "divisors({})", n);
+
+            let mut unsorted = divisors_unsorted(n).collect::<Vec<_>>();
+            unsorted.sort();
+            assert_eq!(unsorted, expected, "divisors_unsorted({})", n);
+
+            assert_eq!(proper_divisors(n), &expected[..expected.len() - 1], "proper_divisors({})", n);
+            assert_eq!(sum_of_divisors(n), expected.iter().sum::<u64>(), "sum_of_divisors({})", n);
+        }
+    }
+
+    #[test]
+    fn divisors_for_zero_is_empty() {
+        assert_eq!(divisors(0), vec![]);
+        assert_eq!(divisors_unsorted(0).count(), 0);
+    }
+
+    #[test]
+    fn sum_of_proper_divisors_works() {
+        assert_eq!(sum_of_proper_divisors(1), 0);
+        assert_eq!(sum_of_proper_divisors(28), 28);
+        assert_eq!(sum_of_proper_divisors(220), 284);
+        assert_eq!(sum_of_proper_divisors(284), 220);
+        assert_eq!(sum_of_proper_divisors(12), 16);
+    }
+
+    #[test]
+    fn num_divisors_works() {
+        assert_eq!(num_divisors(1), 1);
+        assert_eq!(num_divisors(2), 2);
+        assert_eq!(num_divisors(3), 2);
+        assert_eq!(num_divisors(4), 3);
+        assert_eq!(num_divisors(5), 2);
+        assert_eq!(num_divisors(6), 4);
+        assert_eq!(num_divisors(7), 2);
+        assert_eq!(num_divisors(8), 4);
+        assert_eq!(num_divisors(9), 3);
+        assert_eq!(num_divisors(10), 4);
+        assert_eq!(num_divisors(11), 2);
+        assert_eq!(num_divisors(12), 6);
+        assert_eq!(num_divisors(13), 2);
+        assert_eq!(num_divisors(14), 4);
+        assert_eq!(num_divisors(15), 4);
+        assert_eq!(num_divisors(16), 5);
+        assert_eq!(num_divisors(28), 6);
+        assert_eq!(num_divisors(76_576_500), 576);
+        assert_eq!(num_divisors(600_851_475_143), 16);
+    }
+
+    #[test]
+    fn num_divisors_matches_divisors() {
+        for n in 1..100_001 {
+            assert_eq!(num_divisors(n), divisors(n).len() as u64, "num_divisors({})", n);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::useless_vec)]
+    fn vec_to_num_works() {
+        assert_eq!(vec_to_num(&vec![0]), 0);
+        assert_eq!(vec_to_num(&vec![1]), 1);
+        assert_eq!(vec_to_num(&vec![9]), 9);
+        assert_eq!(vec_to_num(&vec![1, 0]), 10);
+        assert_eq!(vec_to_num(&vec![1, 2, 3, 0]), 1230);
+        assert_eq!(vec_to_num(&vec![5, 5, 3, 7, 3, 7, 6, 2, 3, 0]), 5537376230);
+    }
+
+    #[test]
+    fn checked_triangle_works() {
+        assert_eq!(checked_triangle(500), Some(125_250));
+        assert_eq!(checked_triangle(6_074_000_999), Some(18_446_744_070_963_499_500));
+        assert_eq!(checked_triangle(6_074_001_000), None);
+    }
+}
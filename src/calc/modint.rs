@@ -0,0 +1,388 @@
+use std::fmt;
+use std::iter::{Sum, Product};
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+use calc::number_theory::mod_inverse;
+
+/// The modulus most often used by Project Euler for answers that would otherwise be huge.
+pub const MOD_1E9_7: u64 = 1_000_000_007;
+
+/// A modulus for `ModInt`, which decides how values are stored and multiplied.
+pub trait Modulus: Copy + PartialEq + Eq {
+    /// The modulus itself.
+    fn modulus(&self) -> u64;
+
+    /// Converts n into the internal representation.
+    #[inline]
+    fn encode(&self, n: u64) -> u64 {
+        n % self.modulus()
+    }
+
+    /// Converts the internal representation back into a value in 0..modulus.
+    #[inline]
+    fn decode(&self, repr: u64) -> u64 {
+        repr
+    }
+
+    /// Multiplies two values in the internal representation.
+    #[inline]
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        (u128::from(a) * u128::from(b) % u128::from(self.modulus())) as u64
+    }
+}
+
+/// A modulus fixed at compile time, such as `ConstModulus<MOD_1E9_7>`. This takes no space,
+/// and `ModInt::new` can be used to make values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ConstModulus<const M: u64>;
+
+impl<const M: u64> Modulus for ConstModulus<M> {
+    #[inline]
+    fn modulus(&self) -> u64 {
+        M
+    }
+}
+
+/// A modulus chosen at run time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DynamicModulus(u64);
+
+impl DynamicModulus {
+    pub fn new(m: u64) -> Self {
+        assert!(m > 0, "The modulus must be positive");
+        DynamicModulus(m)
+    }
+}
+
+impl Modulus for DynamicModulus {
+    #[inline]
+    fn modulus(&self) -> u64 {
+        self.0
+    }
+}
+
+/// An odd modulus chosen at run time, using Montgomery multiplication with R = 2^64. Values
+/// are held as aR mod n, which replaces the 128-bit division in every multiplication with
+/// two multiplications and a shift. Worth it when doing a lot of multiplications, such as
+/// in `pow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MontgomeryModulus {
+    n: u64,
+    // -n^-1 mod 2^64.
+    n_neg_inv: u64,
+    // R^2 mod n, used to convert into Montgomery form.
+    r2: u64
+}
+
+impl MontgomeryModulus {
+    pub fn new(n: u64) -> Self {
+        assert!(n % 2 == 1, "Montgomery multiplication needs an odd modulus");
+
+        // Newton's iteration doubles the number of correct low bits each time, and n is its
+        // own inverse mod 8, so 5 iterations give 3 * 2^5 >= 64 bits.
+        let mut inv = n;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(n.wrapping_mul(inv)));
+        }
+
+        let r = (1_u128 << 64) % u128::from(n);
+        let r2 = (r * r % u128::from(n)) as u64;
+        MontgomeryModulus { n, n_neg_inv: inv.wrapping_neg(), r2 }
+    }
+
+    /// Returns t * R^-1 mod n, for t < nR.
+    #[inline]
+    fn reduce(&self, t: u128) -> u64 {
+        let m = (t as u64).wrapping_mul(self.n_neg_inv);
+        let (sum, overflow) = t.overflowing_add(u128::from(m) * u128::from(self.n));
+        // The low half of sum is zero, so only the high half (and any carry) is left.
+        let high = sum >> 64;
+        if overflow {
+            (high + (1 << 64) - u128::from(self.n)) as u64
+        } else if high >= u128::from(self.n) {
+            (high - u128::from(self.n)) as u64
+        } else {
+            high as u64
+        }
+    }
+}
+
+impl Modulus for MontgomeryModulus {
+    #[inline]
+    fn modulus(&self) -> u64 {
+        self.n
+    }
+
+    #[inline]
+    fn encode(&self, n: u64) -> u64 {
+        self.mul(n % self.n, self.r2)
+    }
+
+    #[inline]
+    fn decode(&self, repr: u64) -> u64 {
+        self.reduce(u128::from(repr))
+    }
+
+    #[inline]
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(u128::from(a) * u128::from(b))
+    }
+}
+
+/// An integer modulo `M`, where `M` is one of `ConstModulus`, `DynamicModulus` or
+/// `MontgomeryModulus`. Arithmetic between values with different moduli is a bug, and
+/// panics in debug builds.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModInt<M> {
+    repr: u64,
+    modulus: M
+}
+
+impl<M: Modulus + Default> ModInt<M> {
+    pub fn new(n: u64) -> Self {
+        Self::with_modulus(n, M::default())
+    }
+}
+
+impl<M: Modulus> ModInt<M> {
+    pub fn with_modulus(n: u64, modulus: M) -> Self {
+        ModInt { repr: modulus.encode(n), modulus }
+    }
+
+    /// Returns the value, in the range 0..modulus.
+    #[inline]
+    pub fn value(self) -> u64 {
+        self.modulus.decode(self.repr)
+    }
+
+    #[inline]
+    pub fn modulus(self) -> u64 {
+        self.modulus.modulus()
+    }
+
+    /// Computes self^exp by repeated squaring.
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = Self::with_modulus(1, self.modulus);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse, or None if the value and the modulus are not coprime.
+    pub fn inv(self) -> Option<Self> {
+        mod_inverse(self.value(), self.modulus()).map(|n| Self::with_modulus(n, self.modulus))
+    }
+
+    #[inline]
+    fn same_modulus(self, other: Self) -> M {
+        debug_assert!(self.modulus == other.modulus, "ModInts have different moduli");
+        self.modulus
+    }
+}
+
+impl<M: Modulus> Add for ModInt<M> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        // The representations are all below m, so this is just a modular add, which must
+        // not overflow even when m is close to 2^64.
+        let modulus = self.same_modulus(other);
+        let m = modulus.modulus();
+        let repr = if self.repr >= m - other.repr { self.repr - (m - other.repr) } else { self.repr + other.repr };
+        ModInt { repr, modulus }
+    }
+}
+
+impl<M: Modulus> Sub for ModInt<M> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        let modulus = self.same_modulus(other);
+        let repr = if self.repr >= other.repr { self.repr - other.repr } else { modulus.modulus() - (other.repr - self.repr) };
+        ModInt { repr, modulus }
+    }
+}
+
+impl<M: Modulus> Mul for ModInt<M> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        let modulus = self.same_modulus(other);
+        ModInt { repr: modulus.mul(self.repr, other.repr), modulus }
+    }
+}
+
+impl<M: Modulus> Div for ModInt<M> {
+    type Output = Self;
+
+    /// Multiplies by the inverse of other. Panics if other has no inverse.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self.same_modulus(other);
+        self * other.inv().expect("Division by a value which is not coprime to the modulus")
+    }
+}
+
+impl<M: Modulus> Neg for ModInt<M> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        let repr = if self.repr == 0 { 0 } else { self.modulus.modulus() - self.repr };
+        ModInt { repr, modulus: self.modulus }
+    }
+}
+
+impl<M: Modulus> AddAssign for ModInt<M> {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<M: Modulus> SubAssign for ModInt<M> {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<M: Modulus> MulAssign for ModInt<M> {
+    #[inline]
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl<M: Modulus> DivAssign for ModInt<M> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl<M: Modulus + Default> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(0), Add::add)
+    }
+}
+
+impl<M: Modulus + Default> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(1), Mul::mul)
+    }
+}
+
+impl<M: Modulus> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl<M: Modulus> fmt::Debug for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value(), self.modulus())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Mod7 = ModInt<ConstModulus<7>>;
+    type ModP = ModInt<ConstModulus<MOD_1E9_7>>;
+
+    #[test]
+    fn arithmetic_works() {
+        let a = Mod7::new(5);
+        let b = Mod7::new(4);
+        assert_eq!((a + b).value(), 2);
+        assert_eq!((a - b).value(), 1);
+        assert_eq!((b - a).value(), 6);
+        assert_eq!((a * b).value(), 6);
+        assert_eq!((a / b).value(), 3); // 3 * 4 = 12 = 5 (mod 7).
+        assert_eq!((-a).value(), 2);
+        assert_eq!((-Mod7::new(0)).value(), 0);
+        assert_eq!(Mod7::new(100).value(), 2);
+
+        let mut c = a;
+        c += b;
+        c *= b;
+        c -= a;
+        c /= b;
+        assert_eq!(c, (a + b) * b / b - a / b);
+    }
+
+    #[test]
+    fn pow_and_inv_work() {
+        assert_eq!(ModP::new(2).pow(0).value(), 1);
+        assert_eq!(ModP::new(2).pow(10).value(), 1024);
+        // Fermat's little theorem.
+        assert_eq!(ModP::new(123_456).pow(MOD_1E9_7 - 1).value(), 1);
+        assert_eq!(ModP::new(2).inv().unwrap().value(), 500_000_004);
+
+        let m = DynamicModulus::new(10);
+        assert_eq!(ModInt::with_modulus(4, m).inv(), None);
+        assert_eq!(ModInt::with_modulus(3, m).inv().unwrap().value(), 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dividing_by_non_invertible_panics() {
+        let m = DynamicModulus::new(10);
+        let _ = ModInt::with_modulus(3, m) / ModInt::with_modulus(5, m);
+    }
+
+    #[test]
+    fn display_and_debug_work() {
+        let a = ModP::new(MOD_1E9_7 + 12);
+        assert_eq!(format!("{}", a), "12");
+        assert_eq!(format!("{:?}", a), "12 (mod 1000000007)");
+        let m = MontgomeryModulus::new(13);
+        assert_eq!(format!("{}", ModInt::with_modulus(40, m)), "1");
+    }
+
+    #[test]
+    fn sum_and_product_work() {
+        assert_eq!((1..7).map(Mod7::new).sum::<Mod7>().value(), 0);
+        assert_eq!((1..7).map(Mod7::new).product::<Mod7>().value(), 6); // Wilson's theorem.
+    }
+
+    #[test]
+    fn solves_problem_48() {
+        // The last ten digits of 1^1 + 2^2 + ... + 1000^1000.
+        let m = DynamicModulus::new(10_000_000_000);
+        let answer = (1..1001)
+            .map(|i| ModInt::with_modulus(i, m).pow(i))
+            .fold(ModInt::with_modulus(0, m), Add::add);
+        assert_eq!(answer.value(), 9_110_846_700);
+    }
+
+    #[test]
+    fn montgomery_matches_plain_multiplication() {
+        // Small, medium and close-to-2^64 odd moduli, the last being the largest u64 prime.
+        for &n in &[3, 1_000_000_007, (1 << 61) - 1, 18_446_744_073_709_551_557, u64::MAX] {
+            let plain = DynamicModulus::new(n);
+            let mont = MontgomeryModulus::new(n);
+            let values = [0, 1, 2, n / 3, n / 2 + 1, n - 2, n - 1, 0x1234_5678_9abc_def0];
+
+            for &a in &values {
+                for &b in &values {
+                    let (pa, pb) = (ModInt::with_modulus(a, plain), ModInt::with_modulus(b, plain));
+                    let (ma, mb) = (ModInt::with_modulus(a, mont), ModInt::with_modulus(b, mont));
+                    assert_eq!((ma * mb).value(), (pa * pb).value(), "{} * {} mod {}", a, b, n);
+                    assert_eq!((ma + mb).value(), (pa + pb).value(), "{} + {} mod {}", a, b, n);
+                    assert_eq!((ma - mb).value(), (pa - pb).value(), "{} - {} mod {}", a, b, n);
+                    assert_eq!(ma.pow(b).value(), pa.pow(b).value(), "{} ^ {} mod {}", a, b, n);
+                }
+            }
+        }
+    }
+}
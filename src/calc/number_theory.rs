@@ -0,0 +1,443 @@
+use std::fmt::Debug;
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg};
+
+// GCD, LCM, modular inverses and the Chinese Remainder Theorem, for every primitive integer
+// type. Results are always non-negative, and moduli must be positive.
+
+/// The primitive integer types, signed and unsigned, which the functions in this module
+/// work with.
+pub trait Integer: Copy + Ord + Debug
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+    + Div<Output = Self> + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Returns the absolute value. For unsigned types this is the value itself.
+    fn abs(self) -> Self;
+
+    /// Returns the remainder in the range 0..m, for m > 0, even when self is negative.
+    fn rem_euclid(self, m: Self) -> Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+/// The signed integer types, which are needed where results can be negative.
+pub trait SignedInteger: Integer + Neg<Output = Self> {}
+
+/// Integers and references to them, so that `gcd_all` and `lcm_all` can take slices as
+/// well as iterators of values.
+pub trait IntegerValue {
+    type Int: Integer;
+    fn value(self) -> Self::Int;
+}
+
+macro_rules! impl_integer {
+    (unsigned: $($t:ty),*) => {
+        $(
+            impl_integer_value!($t);
+
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn abs(self) -> Self {
+                    self
+                }
+
+                #[inline]
+                fn rem_euclid(self, m: Self) -> Self {
+                    self % m
+                }
+
+                #[inline]
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                #[inline]
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+    (signed: $($t:ty),*) => {
+        $(
+            impl_integer_value!($t);
+
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                #[inline]
+                fn rem_euclid(self, m: Self) -> Self {
+                    <$t>::rem_euclid(self, m)
+                }
+
+                #[inline]
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                #[inline]
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+
+            impl SignedInteger for $t {}
+        )*
+    };
+}
+
+macro_rules! impl_integer_value {
+    ($t:ty) => {
+        impl IntegerValue for $t {
+            type Int = $t;
+
+            #[inline]
+            fn value(self) -> $t {
+                self
+            }
+        }
+
+        impl<'a> IntegerValue for &'a $t {
+            type Int = $t;
+
+            #[inline]
+            fn value(self) -> $t {
+                *self
+            }
+        }
+    }
+}
+
+impl_integer!(unsigned: u8, u16, u32, u64, u128, usize);
+impl_integer!(signed: i8, i16, i32, i64, i128, isize);
+
+/// Compute greatest common divisor by Euclid's algorithm. gcd(0, m) = m, and the
+/// result is never negative.
+pub fn gcd<T: Integer>(n: T, m: T) -> T {
+    let (mut n, mut m) = (n.abs(), m.abs());
+    while m != T::ZERO {
+        let r = n % m;
+        n = m;
+        m = r;
+    }
+    n
+}
+
+/// Compute the lowest common multiple. lcm(0, m) = 0, and the result is never negative.
+pub fn lcm<T: Integer>(n: T, m: T) -> T {
+    if n == T::ZERO || m == T::ZERO {
+        return T::ZERO;
+    }
+    (n / gcd(n, m) * m).abs()
+}
+
+/// Returns the lowest common multiple, or None if it does not fit in the type.
+pub fn checked_lcm<T: Integer>(n: T, m: T) -> Option<T> {
+    if n == T::ZERO || m == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (n / gcd(n, m)).abs().checked_mul(m.abs())
+}
+
+/// Returns the GCD of all the values, which can be a slice or an iterator. The GCD of
+/// nothing is 0.
+pub fn gcd_all<I>(values: I) -> <I::Item as IntegerValue>::Int
+    where I: IntoIterator, I::Item: IntegerValue
+{
+    values.into_iter().fold(Integer::ZERO, |acc, n| gcd(acc, n.value()))
+}
+
+/// Returns the LCM of all the values, which can be a slice or an iterator. The LCM of
+/// nothing is 1.
+pub fn lcm_all<I>(values: I) -> <I::Item as IntegerValue>::Int
+    where I: IntoIterator, I::Item: IntegerValue
+{
+    values.into_iter().fold(Integer::ONE, |acc, n| lcm(acc, n.value()))
+}
+
+/// Returns (g, x, y) such that a*x + b*y = g = gcd(a, b).
+pub fn extended_gcd<T: SignedInteger>(a: T, b: T) -> (T, T, T) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (T::ONE, T::ZERO);
+    let (mut y0, mut y1) = (T::ZERO, T::ONE);
+
+    while r1 != T::ZERO {
+        let q = r0 / r1;
+        let r2 = r0 - q * r1;
+        r0 = r1;
+        r1 = r2;
+        let x2 = x0 - q * x1;
+        x0 = x1;
+        x1 = x2;
+        let y2 = y0 - q * y1;
+        y0 = y1;
+        y1 = y2;
+    }
+
+    if r0 < T::ZERO {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// Returns x in 0..m such that a*x = 1 (mod m), or None if a and m are not coprime.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    assert!(m > T::ZERO, "The modulus must be positive");
+
+    // Euclid's algorithm on (m, a), keeping track of the coefficient of a. The coefficients
+    // alternate in sign and their sizes never exceed m, so store the size and the sign
+    // separately, which works for unsigned types too.
+    let (mut r0, mut r1) = (m, a.rem_euclid(m));
+    let (mut t0, mut t1) = (T::ZERO, T::ONE);
+    let mut t1_negative = false;
+
+    while r1 != T::ZERO {
+        let q = r0 / r1;
+        let r2 = r0 - q * r1;
+        r0 = r1;
+        r1 = r2;
+        let t2 = t0 + q * t1;
+        t0 = t1;
+        t1 = t2;
+        t1_negative = !t1_negative;
+    }
+
+    if r0 != T::ONE {
+        None
+    } else if t1_negative || t0 == T::ZERO {
+        // t1 is negative, so t0 is positive.
+        Some(t0 % m)
+    } else {
+        Some(m - t0)
+    }
+}
+
+/// Returns a*b mod m without overflowing, for a and b in 0..m.
+fn mul_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+
+    // Double-and-add, keeping every intermediate value below m.
+    let two = T::ONE + T::ONE;
+    let (mut a, mut b) = (a, b);
+    let mut result = T::ZERO;
+    while b != T::ZERO {
+        if b % two == T::ONE {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b = b / two;
+    }
+    result
+}
+
+/// Returns a+b mod m without overflowing, for a and b in 0..m.
+fn add_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// Solves the simultaneous congruences x = r (mod m) for each (r, m) pair using the
+/// Chinese Remainder Theorem. The moduli need not be coprime. Returns (x, l), where l is
+/// the LCM of the moduli and x is in 0..l, so the solutions are exactly x + kl. Returns
+/// None if the congruences contradict each other, or if l does not fit in the type.
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut result = (T::ZERO, T::ONE);
+    for &(r, m) in congruences {
+        assert!(m > T::ZERO, "The modulus must be positive");
+        result = crt_pair(result, (r.rem_euclid(m), m))?;
+    }
+    Some(result)
+}
+
+/// Combines x = a1 (mod m1) and x = a2 (mod m2), with a1 and a2 already reduced.
+fn crt_pair<T: Integer>((a1, m1): (T, T), (a2, m2): (T, T)) -> Option<(T, T)> {
+    // x = a1 + m1*k, so we need m1*k = a2 - a1 (mod m2). That is only solvable if
+    // g = gcd(m1, m2) divides a2 - a1, and then k = (a2 - a1)/g * (m1/g)^-1 (mod m2/g).
+    let g = gcd(m1, m2);
+    let a1_mod_m2 = a1 % m2;
+    let diff = if a2 >= a1_mod_m2 { a2 - a1_mod_m2 } else { a2 + (m2 - a1_mod_m2) };
+    if diff % g != T::ZERO {
+        return None;
+    }
+
+    let m2_g = m2 / g;
+    let inverse = mod_inverse(m1 / g, m2_g).expect("m1/g and m2/g are coprime");
+    let k = mul_mod(diff / g, inverse, m2_g);
+    let l = m1.checked_mul(m2_g)?;
+    Some((m1.checked_mul(k)?.checked_add(a1)?, l))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_works() {
+        assert_eq!(1, gcd(1, 1));
+        assert_eq!(1, gcd(1, 2));
+        assert_eq!(1, gcd(1, 3));
+        assert_eq!(2, gcd(2, 2));
+        assert_eq!(2 * 7, gcd(2 * 3 * 5 * 7, 2 * 7));
+    }
+
+    #[test]
+    fn gcd_handles_zero_and_negatives() {
+        assert_eq!(gcd(0_u64, 12), 12);
+        assert_eq!(gcd(12_u64, 0), 12);
+        assert_eq!(gcd(0_u64, 0), 0);
+        assert_eq!(gcd(-12_i32, 18), 6);
+        assert_eq!(gcd(12_i32, -18), 6);
+        assert_eq!(gcd(-12_i64, 0), 12);
+        assert_eq!(gcd(200_u8, 250), 50);
+        assert_eq!(gcd(u128::MAX, u128::MAX - 1), 1);
+    }
+
+    #[test]
+    fn lcm_works() {
+        assert_eq!(lcm(4_u32, 6), 12);
+        assert_eq!(lcm(0_u32, 6), 0);
+        assert_eq!(lcm(-4_i32, 6), 12);
+        assert_eq!(lcm(7_u64, 7), 7);
+        assert_eq!(lcm(1_u64 << 40, 3 << 20), 3 << 40);
+    }
+
+    #[test]
+    fn checked_lcm_works() {
+        assert_eq!(checked_lcm(4_u32, 6), Some(12));
+        assert_eq!(checked_lcm(0_u32, u32::MAX), Some(0));
+        assert_eq!(checked_lcm(-4_i32, -6), Some(12));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(checked_lcm(1_u64 << 63, 3), None);
+        assert_eq!(checked_lcm(1_u64 << 62, 3), Some(3 << 62));
+        assert_eq!(checked_lcm(i64::MAX, 2), None);
+        assert_eq!(checked_lcm(u8::MAX, 254), None);
+    }
+
+    #[test]
+    fn gcd_and_lcm_of_many_works() {
+        assert_eq!(gcd_all(&[12_u64, 18, 30]), 6);
+        assert_eq!(gcd_all(vec![-12_i32, 18]), 6);
+        assert_eq!(gcd_all(&[] as &[u64]), 0);
+        assert_eq!(lcm_all(&[4_u32, 6, 10]), 60);
+        assert_eq!(lcm_all(1..21_u64), 232_792_560);
+        assert_eq!(lcm_all(Vec::<u64>::new()), 1);
+    }
+
+    #[test]
+    fn extended_gcd_works() {
+        assert_eq!(extended_gcd(240_i64, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(0_i64, 5), (5, 0, 1));
+        assert_eq!(extended_gcd(5_i64, 0), (5, 1, 0));
+
+        for a in -30_i32..31 {
+            for b in -30_i32..31 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, gcd(a, b), "extended_gcd({}, {})", a, b);
+                assert_eq!(a * x + b * y, g, "extended_gcd({}, {})", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn mod_inverse_matches_brute_force() {
+        for m in 1_u32..60 {
+            for a in 0..m {
+                let expected = (0..m).find(|&x| (a * x) % m == 1 % m);
+                assert_eq!(mod_inverse(a, m), expected, "mod_inverse({}, {})", a, m);
+            }
+        }
+    }
+
+    #[test]
+    fn mod_inverse_works_for_wide_and_signed_types() {
+        assert_eq!(mod_inverse(3_i32, 7), Some(5));
+        assert_eq!(mod_inverse(-3_i32, 7), Some(2));
+        assert_eq!(mod_inverse(4_i64, 8), None);
+        assert_eq!(mod_inverse(255_u8, 254), Some(1));
+
+        let m = 1_000_000_007_u64;
+        let inverse = mod_inverse(2, m).unwrap();
+        assert_eq!(inverse, 500_000_004);
+
+        // The largest u64 prime, where the coefficients get close to the modulus.
+        let p = 18_446_744_073_709_551_557_u64;
+        let inverse = mod_inverse(p - 1, p).unwrap();
+        assert_eq!(inverse, p - 1);
+        let inverse = mod_inverse(123_456_789_u128, u128::from(p)).unwrap();
+        assert_eq!(123_456_789 * inverse % u128::from(p), 1);
+    }
+
+    #[test]
+    fn crt_works_for_coprime_moduli() {
+        assert_eq!(crt(&[(2_u64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(0_u64, 4), (0, 9)]), Some((0, 36)));
+        assert_eq!(crt::<u64>(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(-1_i64, 3), (-1, 5)]), Some((14, 15)));
+    }
+
+    #[test]
+    fn crt_works_for_non_coprime_moduli() {
+        assert_eq!(crt(&[(2_u32, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1_u32, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(3_u32, 6), (3, 6)]), Some((3, 6)));
+        assert_eq!(crt(&[(5_u32, 12), (11, 18), (5, 8)]), Some((29, 72)));
+        assert_eq!(crt(&[(5_u32, 12), (11, 18), (7, 8)]), None);
+    }
+
+    #[test]
+    fn crt_matches_brute_force() {
+        for m1 in 1_u32..13 {
+            for m2 in 1_u32..13 {
+                let l = lcm(m1, m2);
+                for a1 in 0..m1 {
+                    for a2 in 0..m2 {
+                        let expected = (0..l).find(|&x| x % m1 == a1 && x % m2 == a2).map(|x| (x, l));
+                        assert_eq!(crt(&[(a1, m1), (a2, m2)]), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_works_near_the_top_of_the_type() {
+        // The product of the moduli fills most of a u128, so the intermediate products
+        // have to be reduced without overflowing.
+        let p = 18_446_744_073_709_551_557_u128;
+        let q = 18_446_744_073_709_551_533_u128;
+        let (x, l) = crt(&[(q - 2, q), (q - 1, p)]).unwrap();
+        assert_eq!(l, p * q);
+        assert_eq!(x % q, q - 2);
+        assert_eq!(x % p, q - 1);
+    }
+
+    #[test]
+    fn crt_returns_none_when_the_lcm_does_not_fit() {
+        let p = 4_294_967_291_u64;
+        let q = 4_294_967_279_u64;
+        assert!(crt(&[(1, p), (2, q)]).is_some());
+        assert_eq!(crt(&[(1, p), (2, q), (3, 5)]), None);
+        assert_eq!(crt(&[(1_i8, 11), (2, 13)]), None);
+        assert_eq!(crt(&[(1_i8, 11), (2, 7)]), Some((23, 77)));
+        assert_eq!(crt(&[(0_u8, 16), (0, 16)]), Some((0, 16)));
+        assert_eq!(crt(&[(0_u8, 16), (0, 17)]), None);
+    }
+}
@@ -0,0 +1,244 @@
+use std::convert::TryFrom;
+use calc::figurate::PENTAGONAL;
+use prime::cache::PRIMES;
+
+// Counting the ways of writing a number as a sum of parts, where order does not matter.
+// With every positive integer allowed as a part these are the partition numbers p(n).
+// Restricting the parts to a set of coin values gives the coin-change problem, which is
+// solved by the usual dynamic programming table.
+
+/// Returns a table of the number of ways of making each total from 0 to `limit` out of the
+/// given parts, each of which may be used any number of times. Panics if a count does not
+/// fit in a u64.
+pub fn ways_table(limit: usize, parts: &[u64]) -> Vec<u64> {
+    let mut ways = vec![0_u64; limit + 1];
+    ways[0] = 1;
+    for &part in parts {
+        assert!(part > 0, "Parts must be positive");
+        let part = usize::try_from(part).unwrap_or_else(|_| panic!("The part {} does not fit in a usize", part));
+        for total in part..limit + 1 {
+            ways[total] = ways[total].checked_add(ways[total - part])
+                .unwrap_or_else(|| panic!("The number of ways of making {} overflows a u64", total));
+        }
+    }
+    ways
+}
+
+/// Returns the number of ways of making n out of the given parts, each of which may be used
+/// any number of times. For example there are 4 ways of making 5 from 1, 2 and 5.
+pub fn count_ways(n: usize, parts: &[u64]) -> u64 {
+    ways_table(n, parts)[n]
+}
+
+/// Returns a table of the number of ways of making each total from 0 to `limit`, where
+/// each part is given as (part, maximum number of times it may be used). Panics if a count
+/// does not fit in a u64.
+pub fn ways_table_bounded(limit: usize, parts: &[(u64, usize)]) -> Vec<u64> {
+    let mut ways = vec![0_u64; limit + 1];
+    ways[0] = 1;
+    for &(part, max_uses) in parts {
+        assert!(part > 0, "Parts must be positive");
+        let part = usize::try_from(part).unwrap_or_else(|_| panic!("The part {} does not fit in a usize", part));
+
+        // The new count for a total is the sum of the old counts for total, total - part,
+        // ..., total - max_uses * part. That is a sliding window, so each new count is the
+        // one before it plus the old count entering the window, minus the one leaving it.
+        // The one leaving is part of the count before, so subtracting it first cannot
+        // underflow, and the sum only overflows if the new count itself does.
+        let old = ways.clone();
+        for total in part..limit + 1 {
+            let mut count = ways[total - part];
+            if let Some(leaving) = max_uses.checked_add(1).and_then(|uses| uses.checked_mul(part)) {
+                if leaving <= total {
+                    count -= old[total - leaving];
+                }
+            }
+            ways[total] = count.checked_add(old[total])
+                .unwrap_or_else(|| panic!("The number of ways of making {} overflows a u64", total));
+        }
+    }
+    ways
+}
+
+/// Returns the number of ways of making n, where each part is given as (part, maximum
+/// number of times it may be used).
+pub fn count_ways_bounded(n: usize, parts: &[(u64, usize)]) -> u64 {
+    ways_table_bounded(n, parts)[n]
+}
+
+/// Returns the generalised pentagonal numbers up to limit, other than 0, paired with their
+/// sign in Euler's recurrence p(n) = p(n - 1) + p(n - 2) - p(n - 5) - p(n - 7) + ...
+fn pentagonal_terms(limit: usize) -> Vec<(usize, bool)> {
+    PENTAGONAL.generalised().skip(1)
+        .take_while(|&g| g <= limit as u64)
+        .enumerate()
+        .map(|(j, g)| (g as usize, j % 4 < 2))
+        .collect()
+}
+
+/// Returns the partition numbers p(0) to p(limit), using Euler's pentagonal number theorem,
+/// which needs only O(sqrt(n)) earlier values for each one. Panics if a partition number does
+/// not fit in a u64, which happens after p(416).
+pub fn partition_numbers(limit: usize) -> Vec<u64> {
+    let terms = pentagonal_terms(limit);
+    let mut p = vec![1_u64; limit + 1];
+    for n in 1..limit + 1 {
+        // The terms alternate in sign in pairs, so sum in an i128 where they cannot overflow.
+        let sum = terms.iter().take_while(|&&(g, _)| g <= n)
+            .map(|&(g, add)| if add { i128::from(p[n - g]) } else { -i128::from(p[n - g]) })
+            .sum::<i128>();
+        p[n] = u64::try_from(sum).unwrap_or_else(|_| panic!("p({}) overflows a u64", n));
+    }
+    p
+}
+
+/// Returns the partition number p(n), the number of ways of writing n as a sum of positive
+/// integers.
+pub fn partition_count(n: usize) -> u64 {
+    partition_numbers(n)[n]
+}
+
+/// Returns the partition numbers p(0) to p(limit) modulo m, for any limit.
+pub fn partition_numbers_mod(limit: usize, m: u64) -> Vec<u64> {
+    assert!(m > 0, "The modulus must be positive");
+    let terms = pentagonal_terms(limit);
+    let mut p = vec![1 % m; limit + 1];
+    for n in 1..limit + 1 {
+        p[n] = terms.iter().take_while(|&&(g, _)| g <= n).fold(0, |sum, &(g, add)| {
+            let term = p[n - g];
+            // Both are below m, so neither can overflow.
+            if add {
+                if sum >= m - term { sum - (m - term) } else { sum + term }
+            } else if sum >= term {
+                sum - term
+            } else {
+                sum + (m - term)
+            }
+        });
+    }
+    p
+}
+
+/// Returns a table of the number of ways of writing each number from 0 to `limit` as a sum
+/// of primes.
+pub fn prime_partitions_table(limit: usize) -> Vec<u64> {
+    ways_table(limit, &PRIMES.primes_below(limit as u64 + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_ways_works() {
+        assert_eq!(count_ways(5, &[1, 2, 5]), 4);
+        assert_eq!(count_ways(0, &[1, 2, 5]), 1);
+        assert_eq!(count_ways(3, &[2]), 0);
+        assert_eq!(count_ways(3, &[]), 0);
+        assert_eq!(count_ways(10, &[5, 2, 3]), count_ways(10, &[2, 3, 5]));
+        assert_eq!(ways_table(6, &[2, 3]), vec![1, 0, 1, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn count_ways_bounded_works() {
+        // 1, 2 and 2 + 1 only.
+        assert_eq!(ways_table_bounded(4, &[(1, 1), (2, 1)]), vec![1, 1, 1, 1, 0]);
+        assert_eq!(count_ways_bounded(10, &[(1, 0), (2, 5)]), 1);
+        assert_eq!(count_ways_bounded(10, &[(1, usize::MAX), (2, usize::MAX)]), count_ways(10, &[1, 2]));
+
+        // Compare against brute force over every combination of uses.
+        let parts = [(1, 3), (2, 2), (5, 2), (7, 1)];
+        let mut expected = vec![0; 30];
+        for a in 0..4 {
+            for b in 0..3 {
+                for c in 0..3 {
+                    for d in 0..2 {
+                        expected[a + 2 * b + 5 * c + 7 * d] += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(ways_table_bounded(29, &parts), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn ways_table_panics_on_overflow() {
+        let parts = (1..418).collect::<Vec<_>>();
+        ways_table(417, &parts);
+    }
+
+    #[test]
+    fn count_ways_bounded_near_the_limit_works() {
+        // A part which may be used zero times changes nothing, but the window sum for it
+        // passes through p(415) + p(416), which does not fit in a u64.
+        let mut parts = (1..417).map(|part| (part, usize::MAX)).collect::<Vec<_>>();
+        parts.push((1, 0));
+        assert_eq!(ways_table_bounded(416, &parts), partition_numbers(416));
+    }
+
+    #[test]
+    fn partition_numbers_works() {
+        // OEIS A000041.
+        assert_eq!(partition_numbers(12), vec![1, 1, 2, 3, 5, 7, 11, 15, 22, 30, 42, 56, 77]);
+        assert_eq!(partition_count(0), 1);
+        assert_eq!(partition_count(200), 3_972_999_029_388);
+
+        let parts = (1..301).collect::<Vec<_>>();
+        assert_eq!(partition_numbers(300), ways_table(300, &parts));
+    }
+
+    #[test]
+    fn partition_numbers_near_the_limit_works() {
+        let p = partition_numbers(416);
+        let parts = (1..417).collect::<Vec<_>>();
+        assert_eq!(p, ways_table(416, &parts));
+    }
+
+    #[test]
+    #[should_panic]
+    fn partition_numbers_panics_on_overflow() {
+        partition_numbers(417);
+    }
+
+    #[test]
+    fn partition_numbers_mod_works() {
+        let p = partition_numbers(416);
+        for &m in &[1, 2, 7, 1_000_000, u64::MAX] {
+            let expected = p.iter().map(|&x| x % m).collect::<Vec<_>>();
+            assert_eq!(partition_numbers_mod(416, m), expected, "mod {}", m);
+        }
+    }
+
+    #[test]
+    fn prime_partitions_table_works() {
+        // OEIS A000607.
+        assert_eq!(prime_partitions_table(12), vec![1, 0, 1, 1, 1, 2, 2, 3, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn solves_problem_31() {
+        // The ways of making 2 pounds out of British coins.
+        assert_eq!(count_ways(200, &[1, 2, 5, 10, 20, 50, 100, 200]), 73_682);
+    }
+
+    #[test]
+    fn solves_problem_76() {
+        // The ways of writing 100 as a sum of at least two positive integers.
+        assert_eq!(partition_count(100) - 1, 190_569_291);
+    }
+
+    #[test]
+    fn solves_problem_77() {
+        // The first number which can be written as a sum of primes in over 5000 ways.
+        let table = prime_partitions_table(100);
+        assert_eq!(table.iter().position(|&ways| ways > 5000), Some(71));
+    }
+
+    #[test]
+    fn solves_problem_78() {
+        // The least n for which p(n) is divisible by one million.
+        let p = partition_numbers_mod(60_000, 1_000_000);
+        assert_eq!(p.iter().position(|&x| x == 0), Some(55_374));
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt::Debug;
+use std::ops::{Add, Sub, Div, Shl, Shr};
+
+// Exact integer roots. Going through f64 is quick but f64 only has 53 bits of precision,
+// so for large numbers the result can be out by one (or by thousands for a u128), which
+// silently breaks things like prime bounds. Everything here is exact.
+
+/// The unsigned integer types which the root functions work with: u32, u64 and u128.
+pub trait RootInt: Copy + Ord + Debug
+    + Add<Output = Self> + Sub<Output = Self> + Div<Output = Self>
+    + Shl<u32, Output = Self> + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const BITS: u32;
+
+    fn leading_zeros(self) -> u32;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+    fn rem_u32(self, m: u32) -> u32;
+    fn to_f64(self) -> f64;
+    /// Converts back from f64, saturating at 0 and MAX.
+    fn from_f64(x: f64) -> Self;
+}
+
+macro_rules! impl_root_int {
+    ($t:ty) => {
+        impl RootInt for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const BITS: u32 = <$t>::BITS;
+
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                <$t>::leading_zeros(self)
+            }
+
+            #[inline]
+            fn checked_pow(self, exp: u32) -> Option<Self> {
+                <$t>::checked_pow(self, exp)
+            }
+
+            #[inline]
+            fn rem_u32(self, m: u32) -> u32 {
+                (self % m as $t) as u32
+            }
+
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            #[inline]
+            fn from_f64(x: f64) -> Self {
+                x as $t
+            }
+        }
+    }
+}
+
+impl_root_int!(u32);
+impl_root_int!(u64);
+impl_root_int!(u128);
+
+/// Returns floor(sqrt(n)).
+pub fn isqrt<T: RootInt>(n: T) -> T {
+    if n < T::ONE + T::ONE {
+        return n;
+    }
+
+    // Newton's method converges downwards onto the root from any starting point above it,
+    // and 2^ceil(bits / 2) is always above it.
+    let bits = T::BITS - n.leading_zeros();
+    let mut x = T::ONE << bits.div_ceil(2);
+    loop {
+        let y = (x + n / x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Returns floor(cbrt(n)).
+pub fn icbrt<T: RootInt>(n: T) -> T {
+    iroot(n, 3)
+}
+
+/// Returns floor(n^(1/k)), the largest r with r^k <= n.
+pub fn iroot<T: RootInt>(n: T, k: u32) -> T {
+    assert!(k > 0, "There is no 0th root");
+    if k == 1 || n < T::ONE + T::ONE {
+        return n;
+    }
+    if k == 2 {
+        return isqrt(n);
+    }
+    if k >= T::BITS {
+        // 2^k is too big for the type, so only 1 is small enough.
+        return T::ONE;
+    }
+
+    // For k >= 3 the root has at most 43 bits, so the f64 estimate is within one or two
+    // and just needs nudging into place.
+    let mut r = T::from_f64(n.to_f64().powf(1.0 / f64::from(k)));
+    while r.checked_pow(k).is_none_or(|p| p > n) {
+        r = r - T::ONE;
+    }
+    while (r + T::ONE).checked_pow(k).is_some_and(|p| p <= n) {
+        r = r + T::ONE;
+    }
+    r
+}
+
+/// Returns a bitmask of the squares modulo m, for m <= 128.
+const fn square_residues(m: u32) -> u128 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < m {
+        mask |= 1 << (i * i % m);
+        i += 1;
+    }
+    mask
+}
+
+const SQUARES_MOD_64: u128 = square_residues(64);
+const SQUARES_MOD_63: u128 = square_residues(63);
+const SQUARES_MOD_65: u128 = square_residues(65);
+const SQUARES_MOD_11: u128 = square_residues(11);
+
+/// Returns true if n is a perfect square. Only 12 of the 64 residues mod 64 are squares,
+/// and similarly for 63, 65 and 11, so those checks reject over 99% of non-squares without
+/// taking the square root.
+pub fn is_perfect_square<T: RootInt>(n: T) -> bool {
+    let is_residue = |mask: u128, m: u32| mask & (1 << n.rem_u32(m)) != 0;
+    if !is_residue(SQUARES_MOD_64, 64) || !is_residue(SQUARES_MOD_63, 63)
+        || !is_residue(SQUARES_MOD_65, 65) || !is_residue(SQUARES_MOD_11, 11)
+    {
+        return false;
+    }
+
+    let r = isqrt(n);
+    r.checked_pow(2) == Some(n)
+}
+
+/// If n = b^k for some k >= 2, returns (b, k) with the largest such k. 0 and 1 are powers
+/// with every exponent, and are returned as (n, 2).
+pub fn perfect_power<T: RootInt>(n: T) -> Option<(T, u32)> {
+    if n < T::ONE + T::ONE {
+        return Some((n, 2));
+    }
+
+    // The exponent cannot exceed log2(n).
+    let max_k = T::BITS - n.leading_zeros() - 1;
+    (2..max_k + 1).rev()
+        .map(|k| (iroot(n, k), k))
+        .find(|&(r, k)| r.checked_pow(k) == Some(n))
+}
+
+/// Returns true if n = b^k for some k >= 2.
+pub fn is_perfect_power<T: RootInt>(n: T) -> bool {
+    perfect_power(n).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the roots either side of the boundary at r^k.
+    fn check_boundary<T: RootInt>(r: T, k: u32) {
+        let n = r.checked_pow(k).unwrap();
+        assert_eq!(iroot(n, k), r, "iroot({:?}, {})", n, k);
+        assert_eq!(iroot(n - T::ONE, k), r - T::ONE, "iroot({:?} - 1, {})", n, k);
+        if let Some(next) = (r + T::ONE).checked_pow(k) {
+            assert_eq!(iroot(next - T::ONE, k), r, "iroot({:?} - 1, {})", next, k);
+        }
+    }
+
+    #[test]
+    fn isqrt_matches_brute_force() {
+        let mut r = 0_u64;
+        for n in 0_u64..100_000 {
+            if (r + 1) * (r + 1) <= n {
+                r += 1;
+            }
+            assert_eq!(isqrt(n), r, "isqrt({})", n);
+            assert_eq!(isqrt(n as u32), r as u32, "isqrt({})", n);
+        }
+    }
+
+    #[test]
+    fn isqrt_is_exact_at_every_square_boundary() {
+        for r in 1_u64..100_000 {
+            check_boundary(r, 2);
+        }
+        // The boundaries where f64 starts to go wrong, and the top of each type.
+        for r in (1_u64 << 26) - 1000..(1 << 26) + 1000 {
+            check_boundary(r, 2);
+        }
+        for r in u64::from(u32::MAX) - 1000..u64::from(u32::MAX) + 1 {
+            check_boundary(r, 2);
+            check_boundary(u128::from(r) << 32, 2);
+        }
+        for r in u128::from(u64::MAX) - 1000..u128::from(u64::MAX) + 1 {
+            check_boundary(r, 2);
+        }
+        assert_eq!(isqrt(u32::MAX), u32::from(u16::MAX));
+        assert_eq!(isqrt(u64::MAX), u64::from(u32::MAX));
+        assert_eq!(isqrt(u128::MAX), u128::from(u64::MAX));
+    }
+
+    #[test]
+    fn iroot_matches_brute_force() {
+        for k in 1..8 {
+            let mut r = 0_u64;
+            for n in 0_u64..20_000 {
+                if (r + 1).pow(k) <= n {
+                    r += 1;
+                }
+                assert_eq!(iroot(n, k), r, "iroot({}, {})", n, k);
+            }
+        }
+    }
+
+    #[test]
+    fn iroot_is_exact_at_large_boundaries() {
+        for k in 3..10 {
+            let top = iroot(u64::MAX, k);
+            for r in top.saturating_sub(100).max(2)..top + 1 {
+                check_boundary(r, k);
+            }
+            let top = iroot(u128::MAX, k);
+            for r in top - 100..top + 1 {
+                check_boundary(r, k);
+            }
+        }
+        assert_eq!(icbrt(u64::MAX), 2_642_245);
+        assert_eq!(icbrt(u128::MAX), 6_981_463_658_331);
+        assert_eq!(iroot(u128::MAX, 5), 50_859_008);
+        assert_eq!(iroot(u64::MAX, 63), 2);
+        assert_eq!(iroot(u64::MAX, 64), 1);
+        assert_eq!(iroot(u64::MAX, 1000), 1);
+    }
+
+    #[test]
+    fn is_perfect_square_matches_isqrt() {
+        for n in 0_u64..100_000 {
+            let r = isqrt(n);
+            assert_eq!(is_perfect_square(n), r * r == n, "is_perfect_square({})", n);
+        }
+        let r = u128::from(u64::MAX);
+        assert!(is_perfect_square(r * r));
+        assert!(!is_perfect_square(r * r - 1));
+        assert!(!is_perfect_square(u64::MAX));
+        assert!(is_perfect_square(u64::from(u32::MAX) * u64::from(u32::MAX)));
+    }
+
+    #[test]
+    fn perfect_power_works() {
+        // OEIS A001597.
+        let powers = (0_u64..150).filter(|&n| is_perfect_power(n)).collect::<Vec<_>>();
+        assert_eq!(powers, vec![0, 1, 4, 8, 9, 16, 25, 27, 32, 36, 49, 64, 81, 100, 121, 125, 128, 144]);
+        assert_eq!((2_u64..1001).filter(|&n| is_perfect_power(n)).count(), 40);
+
+        assert_eq!(perfect_power(64_u64), Some((2, 6)));
+        assert_eq!(perfect_power(1_u64 << 63), Some((2, 63)));
+        assert_eq!(perfect_power(3_u64.pow(40)), Some((3, 40)));
+        assert_eq!(perfect_power(6_u128.pow(49)), Some((6, 49)));
+        assert_eq!(perfect_power(u64::MAX), None);
+        assert_eq!(perfect_power(12_u32), None);
+    }
+}
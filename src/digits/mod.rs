@@ -0,0 +1,456 @@
+use overflow;
+
+pub mod pandigital;
+
+// Digits of numbers in any base from 2 to 36, worked out arithmetically rather than by
+// formatting the number into a String. Digits are u32s, the same as `char::to_digit`.
+
+/// An iterator over the digits of a number, least significant first. It is double-ended,
+/// so `.rev()` gives the digits most significant first. 0 has a single digit, 0.
+#[derive(Debug, Clone)]
+pub struct Digits {
+    // The digits not yet yielded.
+    n: u64,
+    base: u64,
+    // base^(len - 1), the place value of the most significant remaining digit.
+    high: u64,
+    len: u32
+}
+
+/// Returns an iterator over the digits of n in the given base, least significant first.
+///
+///     assert_eq!(digits(1230, 10).collect::<Vec<_>>(), vec![0, 3, 2, 1]);
+///     assert_eq!(digits(1230, 10).rev().collect::<Vec<_>>(), vec![1, 2, 3, 0]);
+///
+pub fn digits(n: u64, base: u32) -> Digits {
+    check_base(base);
+    let len = digit_count(n, base);
+    let base = u64::from(base);
+    Digits { n, base, high: base.pow(len - 1), len }
+}
+
+impl Iterator for Digits {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let digit = self.n % self.base;
+        self.n /= self.base;
+        self.high /= self.base;
+        self.len -= 1;
+        Some(digit as u32)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len as usize, Some(self.len as usize))
+    }
+}
+
+impl DoubleEndedIterator for Digits {
+    #[inline]
+    fn next_back(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let digit = self.n / self.high;
+        self.n %= self.high;
+        self.high /= self.base;
+        self.len -= 1;
+        Some(digit as u32)
+    }
+}
+
+impl ExactSizeIterator for Digits {}
+
+/// Returns the number of digits in n. 0 has one digit.
+pub fn digit_count(mut n: u64, base: u32) -> u32 {
+    check_base(base);
+    let base = u64::from(base);
+    let mut count = 1;
+    while n >= base {
+        n /= base;
+        count += 1;
+    }
+    count
+}
+
+/// Returns the sum of the digits of n.
+pub fn digit_sum(n: u64, base: u32) -> u64 {
+    digits(n, base).map(u64::from).sum()
+}
+
+/// Builds a number from its digits, most significant first.
+///
+///     assert_eq!(from_digits(vec![1, 2, 3, 0], 10), 1230);
+///
+pub fn from_digits<I: IntoIterator<Item = u32>>(digits: I, base: u32) -> u64 {
+    check_base(base);
+    digits.into_iter().fold(0, |n, d| {
+        assert!(d < base, "{} is not a base {} digit", d, base);
+        overflow::add(overflow::mul(n, u64::from(base)), u64::from(d))
+    })
+}
+
+/// As `from_digits`, but returns None if the number does not fit in a u64.
+pub fn checked_from_digits<I: IntoIterator<Item = u32>>(digits: I, base: u32) -> Option<u64> {
+    check_base(base);
+    digits.into_iter().try_fold(0_u64, |n, d| {
+        assert!(d < base, "{} is not a base {} digit", d, base);
+        n.checked_mul(u64::from(base))?.checked_add(u64::from(d))
+    })
+}
+
+/// Returns n with its digits reversed. Trailing zeros become leading zeros and so are
+/// lost: 1230 reverses to 321.
+pub fn reverse_digits(n: u64, base: u32) -> u64 {
+    from_digits(digits(n, base), base)
+}
+
+/// As `reverse_digits`, but returns None if the result does not fit in a u64.
+pub fn checked_reverse_digits(n: u64, base: u32) -> Option<u64> {
+    checked_from_digits(digits(n, base), base)
+}
+
+/// Rotates the digits of n left by k places, so the leading digits move to the end:
+/// 1234 rotated left by 1 is 2341. A zero rotated to the front is lost, so 1023 rotated
+/// left by 1 is 231. The result may not fit in a u64, as with `u64::MAX` rotated left by
+/// 1, so use `checked_rotate_digits_left` near the top of the range.
+pub fn rotate_digits_left(n: u64, k: u32, base: u32) -> u64 {
+    let len = digit_count(n, base);
+    rotate_digits_right(n, len - k % len, base)
+}
+
+/// As `rotate_digits_left`, but returns None if the result does not fit in a u64.
+pub fn checked_rotate_digits_left(n: u64, k: u32, base: u32) -> Option<u64> {
+    let len = digit_count(n, base);
+    checked_rotate_digits_right(n, len - k % len, base)
+}
+
+/// Rotates the digits of n right by k places, so the trailing digits move to the front:
+/// 1234 rotated right by 1 is 4123. As with `rotate_digits_left`, the result may not fit
+/// in a u64.
+pub fn rotate_digits_right(n: u64, k: u32, base: u32) -> u64 {
+    let (high, low, shift) = match split_for_rotation(n, k, base) {
+        Some(parts) => parts,
+        None => return n
+    };
+    overflow::add(overflow::mul(low, shift), high)
+}
+
+/// As `rotate_digits_right`, but returns None if the result does not fit in a u64.
+pub fn checked_rotate_digits_right(n: u64, k: u32, base: u32) -> Option<u64> {
+    let (high, low, shift) = match split_for_rotation(n, k, base) {
+        Some(parts) => parts,
+        None => return Some(n)
+    };
+    low.checked_mul(shift)?.checked_add(high)
+}
+
+/// Splits n for a rotation right by k places into its leading digits, its last k digits
+/// and the power of the base which moves those to the front. Returns None if the rotation
+/// leaves n unchanged, where that power would be base^len and might not fit.
+fn split_for_rotation(n: u64, k: u32, base: u32) -> Option<(u64, u64, u64)> {
+    let len = digit_count(n, base);
+    let k = k % len;
+    if k == 0 {
+        return None;
+    }
+
+    let base = u64::from(base);
+    let low = base.pow(k);
+    Some((n / low, n % low, base.pow(len - k)))
+}
+
+/// Returns how many times each digit occurs in n, indexed by digit. Two numbers are
+/// permutations of each other's digits exactly when their histograms are equal.
+pub fn digit_histogram(n: u64, base: u32) -> [u8; 36] {
+    let mut histogram = [0; 36];
+    for d in digits(n, base) {
+        histogram[d as usize] += 1;
+    }
+    histogram
+}
+
+/// Returns true if n reads the same forwards and backwards in the given base. Only half
+/// the digits are reversed, so this cannot overflow.
+pub fn is_palindrome_in_base(mut n: u64, base: u32) -> bool {
+    check_base(base);
+    let base = u64::from(base);
+    // A trailing zero would have to match a leading zero.
+    if n.is_multiple_of(base) {
+        return n == 0;
+    }
+
+    let mut reversed = 0;
+    while n > reversed {
+        reversed = reversed * base + n % base;
+        n /= base;
+    }
+
+    // With an odd number of digits the middle one ends up on the end of reversed.
+    n == reversed || n == reversed / base
+}
+
+/// Returns the palindromes with exactly `num_digits` digits in the given base, in ascending
+/// order. Use `.rev()` to get them in descending order, which is handy when looking for
+/// the largest palindrome with some property. Palindromes which do not fit in a u64 are
+/// left out.
+pub fn palindromes(num_digits: u32, base: u32) -> impl DoubleEndedIterator<Item = u64> {
+    check_base(base);
+    assert!(num_digits > 0, "Palindromes need at least one digit");
+
+    // Each palindrome is determined by its first ceil(num_digits / 2) digits.
+    let half_len = num_digits.div_ceil(2);
+    let odd = num_digits % 2 == 1;
+    let big_base = u64::from(base);
+    let low = if num_digits == 1 { Some(0) } else { big_base.checked_pow(half_len - 1) };
+    let high = big_base.checked_pow(half_len).unwrap_or(u64::MAX);
+
+    // The palindromes increase with their first half, so find the first half which is too
+    // big by binary search.
+    let mirror = move |half| mirror_digits(half, odd, big_base);
+    let (mut fits, mut too_big) = (low.unwrap_or(high), high);
+    while fits < too_big {
+        let mid = fits + (too_big - fits) / 2;
+        if mirror(mid).is_some() {
+            fits = mid + 1;
+        } else {
+            too_big = mid;
+        }
+    }
+
+    (low.unwrap_or(high)..fits).map(move |half| mirror(half).unwrap())
+}
+
+/// Appends the digits of half to itself in reverse, skipping the last digit of half if
+/// the palindrome is to have an odd number of digits.
+fn mirror_digits(half: u64, odd: bool, base: u64) -> Option<u64> {
+    let mut result = half;
+    let mut rest = if odd { half / base } else { half };
+    while rest > 0 {
+        result = result.checked_mul(base)?.checked_add(rest % base)?;
+        rest /= base;
+    }
+    Some(result)
+}
+
+#[inline]
+fn check_base(base: u32) {
+    assert!((2..37).contains(&base), "The base must be between 2 and 36, not {}", base);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_works() {
+        assert_eq!(digits(1230, 10).collect::<Vec<_>>(), vec![0, 3, 2, 1]);
+        assert_eq!(digits(1230, 10).rev().collect::<Vec<_>>(), vec![1, 2, 3, 0]);
+        assert_eq!(digits(0, 10).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(digits(6, 2).rev().collect::<Vec<_>>(), vec![1, 1, 0]);
+        assert_eq!(digits(255, 16).collect::<Vec<_>>(), vec![15, 15]);
+        assert_eq!(digits(35, 36).collect::<Vec<_>>(), vec![35]);
+        assert_eq!(digits(u64::MAX, 10).len(), 20);
+        assert_eq!(digits(u64::MAX, 2).len(), 64);
+    }
+
+    #[test]
+    fn digits_can_be_taken_from_both_ends() {
+        let mut d = digits(12_345, 10);
+        assert_eq!(d.next(), Some(5));
+        assert_eq!(d.next_back(), Some(1));
+        assert_eq!(d.next(), Some(4));
+        assert_eq!(d.next_back(), Some(2));
+        assert_eq!(d.len(), 1);
+        assert_eq!(d.next_back(), Some(3));
+        assert_eq!(d.next(), None);
+        assert_eq!(d.next_back(), None);
+
+        // Zeros in the middle and at the ends must survive.
+        let mut d = digits(100_200, 10);
+        assert_eq!(d.next_back(), Some(1));
+        assert_eq!(d.next_back(), Some(0));
+        assert_eq!(d.next(), Some(0));
+        assert_eq!(d.collect::<Vec<_>>(), vec![0, 2, 0]);
+    }
+
+    #[test]
+    fn digits_matches_formatting() {
+        for n in (0..100_000).chain(u64::MAX - 1000..u64::MAX) {
+            let expected = format!("{}", n).chars().map(|c| c.to_digit(10).unwrap()).collect::<Vec<_>>();
+            assert_eq!(digits(n, 10).rev().collect::<Vec<_>>(), expected);
+            let expected = format!("{:b}", n).chars().map(|c| c.to_digit(2).unwrap()).collect::<Vec<_>>();
+            assert_eq!(digits(n, 2).rev().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn base_1_panics() {
+        digits(10, 1);
+    }
+
+    #[test]
+    fn digit_count_works() {
+        assert_eq!(digit_count(0, 10), 1);
+        assert_eq!(digit_count(9, 10), 1);
+        assert_eq!(digit_count(10, 10), 2);
+        assert_eq!(digit_count(999_999, 10), 6);
+        assert_eq!(digit_count(u64::MAX, 10), 20);
+        assert_eq!(digit_count(u64::MAX, 16), 16);
+        assert_eq!(digit_count(8, 2), 4);
+    }
+
+    #[test]
+    fn digit_sum_works() {
+        assert_eq!(digit_sum(0, 10), 0);
+        assert_eq!(digit_sum(1230, 10), 6);
+        assert_eq!(digit_sum(255, 2), 8);
+        assert_eq!(digit_sum(u64::MAX, 10), 87);
+    }
+
+    #[test]
+    fn from_digits_works() {
+        assert_eq!(from_digits(vec![0], 10), 0);
+        assert_eq!(from_digits(vec![1], 10), 1);
+        assert_eq!(from_digits(vec![9], 10), 9);
+        assert_eq!(from_digits(vec![1, 0], 10), 10);
+        assert_eq!(from_digits(vec![1, 2, 3, 0], 10), 1230);
+        assert_eq!(from_digits(vec![5, 5, 3, 7, 3, 7, 6, 2, 3, 0], 10), 5_537_376_230);
+        assert_eq!(from_digits(vec![1, 1, 0], 2), 6);
+        assert_eq!(from_digits(vec![15, 15], 16), 255);
+        assert_eq!(from_digits(Vec::new(), 10), 0);
+        assert_eq!(from_digits(digits(u64::MAX, 7).rev(), 7), u64::MAX);
+    }
+
+    #[test]
+    fn checked_from_digits_works() {
+        assert_eq!(checked_from_digits(digits(u64::MAX, 10).rev(), 10), Some(u64::MAX));
+        assert_eq!(checked_from_digits(digits(u64::MAX, 10).rev().chain(Some(0)), 10), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_digits_rejects_bad_digits() {
+        from_digits(vec![1, 10], 10);
+    }
+
+    #[test]
+    fn reverse_digits_works() {
+        assert_eq!(reverse_digits(0, 10), 0);
+        assert_eq!(reverse_digits(1230, 10), 321);
+        assert_eq!(reverse_digits(12_321, 10), 12_321);
+        assert_eq!(reverse_digits(0b1101, 2), 0b1011);
+        assert_eq!(checked_reverse_digits(u64::MAX, 10), None);
+        assert_eq!(checked_reverse_digits(10_000_000_000_000_000_001, 10), Some(10_000_000_000_000_000_001));
+    }
+
+    #[test]
+    fn rotate_digits_works() {
+        assert_eq!(rotate_digits_left(1234, 1, 10), 2341);
+        assert_eq!(rotate_digits_left(1234, 3, 10), 4123);
+        assert_eq!(rotate_digits_left(1234, 4, 10), 1234);
+        assert_eq!(rotate_digits_left(1023, 1, 10), 231);
+        assert_eq!(rotate_digits_right(1234, 1, 10), 4123);
+        assert_eq!(rotate_digits_right(1234, 6, 10), 3412);
+        assert_eq!(rotate_digits_right(0, 1, 10), 0);
+        assert_eq!(rotate_digits_right(7, 3, 10), 7);
+        assert_eq!(rotate_digits_left(0b1100, 1, 2), 0b1001);
+    }
+
+    #[test]
+    fn rotate_digits_near_the_limit_works() {
+        // Rotating by a whole number of turns does not compute base^len, which would overflow.
+        assert_eq!(rotate_digits_left(u64::MAX, 20, 10), u64::MAX);
+        assert_eq!(rotate_digits_right(u64::MAX, 40, 10), u64::MAX);
+        assert_eq!(rotate_digits_right(u64::MAX, 0, 10), u64::MAX);
+        assert_eq!(rotate_digits_right(u64::MAX, 64, 2), u64::MAX);
+        assert_eq!(rotate_digits_left(u64::MAX, 5, 2), u64::MAX);
+        assert_eq!(rotate_digits_left(u64::MAX, 16, 10), 16_151_844_674_407_370_955);
+        assert_eq!(rotate_digits_right(1 << 63 | 1, 1, 2), 3 << 62);
+
+        assert_eq!(checked_rotate_digits_left(u64::MAX, 20, 10), Some(u64::MAX));
+        assert_eq!(checked_rotate_digits_left(u64::MAX, 16, 10), Some(16_151_844_674_407_370_955));
+        assert_eq!(checked_rotate_digits_left(u64::MAX, 18, 10), Some(15_184_467_440_737_095_516));
+        assert_eq!(checked_rotate_digits_left(u64::MAX, 1, 10), None);
+        assert_eq!(checked_rotate_digits_right(u64::MAX, 1, 10), None);
+        assert_eq!(checked_rotate_digits_right(1234, 1, 10), Some(4123));
+        assert_eq!(checked_rotate_digits_left(1 << 63 | 1, 1, 2), Some(3));
+        // Here the multiplication fits but adding the leading digits gives exactly 2^64.
+        assert_eq!(checked_rotate_digits_right(16_161_844_674_407_370_955, 16, 10), None);
+        assert_eq!(checked_rotate_digits_right(16_151_844_674_407_370_955, 16, 10), Some(u64::MAX));
+    }
+
+    #[test]
+    fn digit_histogram_works() {
+        let h = digit_histogram(1_122_334_000, 10);
+        assert_eq!(&h[..5], &[3, 2, 2, 2, 1]);
+        assert!(h[5..].iter().all(|&c| c == 0));
+        assert_eq!(digit_histogram(1487, 10), digit_histogram(8147, 10));
+        assert_ne!(digit_histogram(1487, 10), digit_histogram(14_870, 10));
+        assert_eq!(digit_histogram(0xff0, 16)[15], 2);
+    }
+
+    #[test]
+    fn is_palindrome_in_base_matches_formatting() {
+        for n in 0..100_000 {
+            let s = format!("{}", n);
+            assert_eq!(is_palindrome_in_base(n, 10), s.chars().rev().collect::<String>() == s, "{}", n);
+            let s = format!("{:b}", n);
+            assert_eq!(is_palindrome_in_base(n, 2), s.chars().rev().collect::<String>() == s, "{:b}", n);
+        }
+
+        assert!(is_palindrome_in_base(10_000_000_000_000_000_001, 10));
+        assert!(!is_palindrome_in_base(u64::MAX, 10));
+        assert!(is_palindrome_in_base(u64::MAX, 2));
+        assert!(is_palindrome_in_base(0x1f_f1, 16));
+    }
+
+    #[test]
+    fn palindromes_works() {
+        assert_eq!(palindromes(1, 10).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+        assert_eq!(palindromes(2, 10).collect::<Vec<_>>(), vec![11, 22, 33, 44, 55, 66, 77, 88, 99]);
+        assert_eq!(palindromes(3, 10).take(4).collect::<Vec<_>>(), vec![101, 111, 121, 131]);
+        assert_eq!(palindromes(4, 10).rev().take(3).collect::<Vec<_>>(), vec![9999, 9889, 9779]);
+        assert_eq!(palindromes(4, 2).collect::<Vec<_>>(), vec![0b1001, 0b1111]);
+    }
+
+    #[test]
+    fn palindromes_matches_is_palindrome_in_base() {
+        for &base in &[2, 3, 10, 16] {
+            let mut expected = (0..200_000).filter(|&n| is_palindrome_in_base(n, base));
+            for num_digits in 1..digit_count(200_000, base) {
+                for p in palindromes(num_digits, base) {
+                    assert_eq!(Some(p), expected.next());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn palindromes_stop_at_the_limit() {
+        // 20 digit palindromes go up to 99999999999999999999, and u64::MAX is
+        // 18446744073709551615, so the last one which fits is 18446744066044764481.
+        assert_eq!(palindromes(20, 10).next_back(), Some(18_446_744_066_044_764_481));
+        assert_eq!(palindromes(20, 10).next(), Some(10_000_000_000_000_000_001));
+        assert_eq!(palindromes(21, 10).next(), None);
+        assert_eq!(palindromes(64, 2).next_back(), Some(u64::MAX));
+        assert_eq!(palindromes(65, 2).count(), 0);
+    }
+
+    #[test]
+    fn solves_problem_36() {
+        // The sum of the numbers below a million which are palindromic in base 10 and base 2.
+        let answer = (1..7)
+            .flat_map(|num_digits| palindromes(num_digits, 10))
+            .filter(|&n| is_palindrome_in_base(n, 2))
+            .sum::<u64>();
+        assert_eq!(answer, 872_187);
+    }
+}
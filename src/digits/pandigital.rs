@@ -0,0 +1,206 @@
+use std::ops::RangeInclusive;
+use digits::digits;
+use calc::combinatorics::{next_permutation, prev_permutation};
+
+// Pandigital numbers use each decimal digit in a range exactly once, such as 2143, which
+// is 1 to 4 pandigital. The digits seen so far are tracked as a bitmask with bit d set for
+// digit d, so checking a number needs no allocation.
+
+/// Returns true if n contains each digit in `range` exactly once and no other digits.
+///
+///     assert!(is_pandigital(2143, 1..=4));
+///
+pub fn is_pandigital(n: u64, range: RangeInclusive<u32>) -> bool {
+    add_digits(0, n) == Some(range_mask(&range))
+}
+
+/// Returns true if the digits of the numbers, written one after the other, contain each
+/// digit in `range` exactly once and no other digits. For example 39 × 186 = 7254 is 1 to 9
+/// pandigital because `is_pandigital_concat(&[39, 186, 7254], 1..=9)`.
+pub fn is_pandigital_concat(numbers: &[u64], range: RangeInclusive<u32>) -> bool {
+    numbers.iter().try_fold(0, |mask, &n| add_digits(mask, n)) == Some(range_mask(&range))
+}
+
+/// Returns a key which is the same for two numbers exactly when they are permutations of
+/// each other's digits, such as 1487 and 8147. Useful for grouping numbers in a HashMap.
+/// Each digit's count is packed into 6 bits.
+pub fn digit_signature(n: u64) -> u64 {
+    digits(n, 10).fold(0, |signature, d| signature + (1 << (6 * d)))
+}
+
+fn range_mask(range: &RangeInclusive<u32>) -> u32 {
+    assert!(*range.end() <= 9, "Pandigital ranges are of decimal digits");
+    range.clone().fold(0, |mask, d| mask | 1 << d)
+}
+
+/// Adds the digits of n to mask, returning None if any of them are already there.
+fn add_digits(mask: u32, n: u64) -> Option<u32> {
+    digits(n, 10).try_fold(mask, |mask, d| {
+        if mask & 1 << d == 0 { Some(mask | 1 << d) } else { None }
+    })
+}
+
+/// An iterator over the numbers which are pandigital in a range of digits, in ascending
+/// order. Use `.rev()` to get them in descending order. Numbers with a leading zero are
+/// not included, so 0 to 9 pandigital numbers start at 1023456789.
+pub struct Pandigitals {
+    front: [u8; 10],
+    back: [u8; 10],
+    len: usize,
+    done: bool
+}
+
+/// Returns an iterator over the numbers which are pandigital in `range`, in ascending order.
+pub fn pandigitals(range: RangeInclusive<u32>) -> Pandigitals {
+    range_mask(&range);
+    assert!(!range.is_empty(), "The range of digits cannot be empty");
+
+    let mut front = [0; 10];
+    let len = range.clone().count();
+    for (i, d) in range.enumerate() {
+        front[i] = d as u8;
+    }
+
+    let mut back = front;
+    back[..len].reverse();
+    if len > 1 && front[0] == 0 {
+        front.swap(0, 1);
+    }
+
+    Pandigitals { front, back, len, done: false }
+}
+
+impl Pandigitals {
+    fn value(digits: &[u8]) -> u64 {
+        digits.iter().fold(0, |n, &d| n * 10 + u64::from(d))
+    }
+}
+
+impl Iterator for Pandigitals {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.done {
+            return None;
+        }
+
+        let n = Self::value(&self.front[..self.len]);
+        self.done = self.front == self.back;
+        next_permutation(&mut self.front[..self.len]);
+        Some(n)
+    }
+}
+
+impl DoubleEndedIterator for Pandigitals {
+    fn next_back(&mut self) -> Option<u64> {
+        if self.done {
+            return None;
+        }
+
+        let n = Self::value(&self.back[..self.len]);
+        self.done = self.front == self.back;
+        prev_permutation(&mut self.back[..self.len]);
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use prime::is_prime;
+
+    #[test]
+    fn is_pandigital_works() {
+        assert!(is_pandigital(2143, 1..=4));
+        assert!(is_pandigital(123_456_789, 1..=9));
+        assert!(is_pandigital(1_406_357_289, 0..=9));
+        assert!(is_pandigital(0, 0..=0));
+        assert!(!is_pandigital(2143, 1..=5));
+        assert!(!is_pandigital(21_435, 1..=4));
+        assert!(!is_pandigital(2243, 1..=4));
+        assert!(!is_pandigital(123_456_780, 1..=9));
+        assert!(!is_pandigital(12_345_678_900, 0..=9));
+    }
+
+    #[test]
+    fn is_pandigital_concat_works() {
+        assert!(is_pandigital_concat(&[39, 186, 7254], 1..=9));
+        assert!(is_pandigital_concat(&[192, 384, 576], 1..=9));
+        assert!(!is_pandigital_concat(&[39, 186, 7255], 1..=9));
+        assert!(!is_pandigital_concat(&[39, 186], 1..=9));
+        assert!(!is_pandigital_concat(&[], 1..=9));
+    }
+
+    #[test]
+    fn digit_signature_works() {
+        assert_eq!(digit_signature(1487), digit_signature(8147));
+        assert_eq!(digit_signature(1487), digit_signature(4817));
+        assert_ne!(digit_signature(1487), digit_signature(1488));
+        assert_ne!(digit_signature(1487), digit_signature(14_870));
+        assert_ne!(digit_signature(0), digit_signature(1));
+        assert_ne!(digit_signature(11), digit_signature(1));
+        // Even the longest runs of a digit stay within their 6 bits.
+        assert_eq!(digit_signature(10_000_000_000_000_000_000), 19 + (1 << 6));
+        assert_eq!(digit_signature(9_999_999_999_999_999_999), 19 << 54);
+    }
+
+    #[test]
+    fn pandigitals_works() {
+        assert_eq!(pandigitals(1..=3).collect::<Vec<_>>(), vec![123, 132, 213, 231, 312, 321]);
+        assert_eq!(pandigitals(1..=3).rev().collect::<Vec<_>>(), vec![321, 312, 231, 213, 132, 123]);
+        assert_eq!(pandigitals(0..=2).collect::<Vec<_>>(), vec![102, 120, 201, 210]);
+        assert_eq!(pandigitals(0..=2).rev().collect::<Vec<_>>(), vec![210, 201, 120, 102]);
+        assert_eq!(pandigitals(7..=7).collect::<Vec<_>>(), vec![7]);
+        assert_eq!(pandigitals(0..=0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(pandigitals(0..=9).next(), Some(1_023_456_789));
+        assert_eq!(pandigitals(0..=9).next_back(), Some(9_876_543_210));
+    }
+
+    #[test]
+    fn pandigitals_from_both_ends_meet_in_the_middle() {
+        let mut p = pandigitals(1..=3);
+        assert_eq!(p.next(), Some(123));
+        assert_eq!(p.next_back(), Some(321));
+        assert_eq!(p.next(), Some(132));
+        assert_eq!(p.next_back(), Some(312));
+        assert_eq!(p.next(), Some(213));
+        assert_eq!(p.next_back(), Some(231));
+        assert_eq!(p.next(), None);
+        assert_eq!(p.next_back(), None);
+    }
+
+    #[test]
+    fn pandigitals_matches_is_pandigital() {
+        for end in 1..6 {
+            let expected = (0..1_000_000).filter(|&n| is_pandigital(n, 0..=end)).collect::<Vec<_>>();
+            assert_eq!(pandigitals(0..=end).collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(pandigitals(1..=9).count(), 362_880);
+    }
+
+    #[test]
+    fn solves_problem_32() {
+        // The sum of the products which can be written as a 1 to 9 pandigital multiplication.
+        let mut products = HashSet::new();
+        for a in 1..100 {
+            for b in a + 1..10_000 / a {
+                if is_pandigital_concat(&[a, b, a * b], 1..=9) {
+                    products.insert(a * b);
+                }
+            }
+        }
+        assert_eq!(products.iter().sum::<u64>(), 45_228);
+    }
+
+    #[test]
+    fn solves_problem_41() {
+        // The largest pandigital prime. Digit sums rule out 8 and 9 digits, but checking
+        // them is quick anyway.
+        let answer = (1..10).rev()
+            .flat_map(|n| pandigitals(1..=n).rev())
+            .find(|&n| is_prime(n))
+            .unwrap();
+        assert_eq!(answer, 7_652_413);
+    }
+}
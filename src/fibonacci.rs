@@ -9,6 +9,7 @@ pub fn fib_in_range(r: Range<u64>) -> Vec<u64> {
 
 /// An iterator that yields values from the Fibonacci sequence.
 /// The values start: 1, 2, 3, 5, 8, 13, 21, 34, 55, 89, ...
+/// It stops after 12200160415121876738, the largest Fibonacci number which fits in a u64.
 pub struct FibonacciIterator {
     prev: u64,
     prev_prev: u64
@@ -24,7 +25,7 @@ impl Iterator for FibonacciIterator {
     type Item = u64;
 
     fn next(&mut self) -> Option<u64> {
-        let fib = self.prev_prev + self.prev;
+        let fib = self.prev_prev.checked_add(self.prev)?;
         self.prev_prev = self.prev;
         self.prev = fib;
         Some(fib)
@@ -33,7 +34,7 @@ impl Iterator for FibonacciIterator {
 
 #[cfg(test)]
 mod tests {
-    use super::fib_in_range;
+    use super::{fib_in_range, FibonacciIterator};
 
     #[test]
     fn fib_in_range_for_empty_range_returns_empty_vec() {
@@ -49,4 +50,16 @@ mod tests {
     fn fib_in_range_for_start_at_nonzero_returns_correct_fibs() {
         assert_eq!(fib_in_range(10..100), vec![13, 21, 34, 55, 89]);
     }
+
+    #[test]
+    fn fibonacci_iterator_stops_before_overflowing() {
+        let fibs = FibonacciIterator::new().collect::<Vec<_>>();
+        assert_eq!(fibs.len(), 92);
+        assert_eq!(fibs.last(), Some(&12_200_160_415_121_876_738));
+
+        let mut iter = FibonacciIterator::new().skip(91);
+        assert_eq!(iter.next(), Some(12_200_160_415_121_876_738));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
 }
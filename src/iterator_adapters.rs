@@ -1,10 +1,9 @@
 use std::ops::Range;
 
-/// Types:
-/// Type 1 - an adapter that returns a single constant, such as a u64
-/// Type 2 - an adapter that returns a single value of the same type as the iterator
-/// Type 3 - an adapter that returns another iterator.
-
+// Types:
+// Type 1 - an adapter that returns a single constant, such as a u64
+// Type 2 - an adapter that returns a single value of the same type as the iterator
+// Type 3 - an adapter that returns another iterator.
 
 // Step 1: Define a trait.
 pub trait IteratorAdapters: Iterator {
@@ -47,7 +46,7 @@ impl<I> Iterator for InRange<I>
         if !self.have_skipped {
             self.have_skipped = true;
 
-            while let Some(x) = self.iter.next() {
+            for x in &mut self.iter {
                 if x >= self.r.start {
                     return Some(x);
                 }
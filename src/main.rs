@@ -7,9 +7,11 @@ extern crate fnv;
 mod fibonacci;
 mod prime;
 mod calc;
+mod digits;
 mod utils;
 mod iterator_adapters;
 mod matrix;
+mod overflow;
 mod p001_to_p010;
 mod p011_to_p020;
 
@@ -26,19 +28,26 @@ fn show_help() {
     println!("    $ projecteuler 2..10          // Runs problems 2 to 10, inclusive");
     println!("    $ projecteuler all            // Runs all problems");
     println!();
+    println!("Set {} to a directory to keep computed primes between runs.", CACHE_DIR_VAR);
+    println!();
 }
 
-fn pnop() -> Option<u64> { None }
-
 static SOLUTIONS: [fn() -> Option<u64>; 14] =
     [
         p001, p002, p003, p004, p005, p006, p007, p008, p009, p010,
         p011, p012, p013, p014
     ];
 
+/// Environment variable which, if set, turns on the on-disk prime cache in that directory.
+const CACHE_DIR_VAR: &str = "PROJECTEULER_CACHE_DIR";
+
 fn main() {
     let problems = parse_arguments();
 
+    if let Some(dir) = std::env::var_os(CACHE_DIR_VAR) {
+        prime::cache::PRIMES.enable_disk_cache(dir.into());
+    }
+
     if problems.is_empty() {
         show_help();
         return;
@@ -48,12 +57,9 @@ fn main() {
     println!("==========  ===============  ===============");
 
     for p in problems {
-        let f = if p > SOLUTIONS.len() { pnop } else { SOLUTIONS[p - 1] };
-
-        if f == pnop {
-            println!("Problem {} has not been solved yet! Ignoring.", p);
-        } else {
-            utils::execute(p, f);
+        match SOLUTIONS.get(p.wrapping_sub(1)) {
+            Some(&f) => utils::execute(p, f),
+            None => println!("Problem {} has not been solved yet! Ignoring.", p)
         }
     }
 }
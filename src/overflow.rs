@@ -0,0 +1,108 @@
+use std::fmt::Display;
+use std::ops::{Add, Mul};
+
+// The overflow policy for calc, digits, fibonacci and prime.
+//
+// * Iterators over growing sequences, such as `FibonacciIterator`, stop at the last value
+//   which fits in their type rather than overflowing.
+// * Functions whose result might not fit have `checked_*` variants which return None.
+// * The plain functions do their arithmetic through `add`, `mul` and `pow` below. These
+//   behave like the ordinary operators, so they panic on overflow in debug builds and
+//   wrap in release builds, unless the crate is built with
+//   `--features checked-arithmetic`, which makes them panic in every build. That is
+//   handy for checking that a fast release-mode run is not quietly producing garbage.
+
+/// The integer types which the overflow helpers work with.
+pub trait OverflowInt: Copy + Display + Add<Output = Self> + Mul<Output = Self> {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+    fn pow(self, exp: u32) -> Self;
+}
+
+macro_rules! impl_overflow_int {
+    ($($t:ty),*) => {
+        $(
+            impl OverflowInt for $t {
+                #[inline]
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                #[inline]
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                #[inline]
+                fn checked_pow(self, exp: u32) -> Option<Self> {
+                    <$t>::checked_pow(self, exp)
+                }
+
+                #[inline]
+                fn pow(self, exp: u32) -> Self {
+                    <$t>::pow(self, exp)
+                }
+            }
+        )*
+    }
+}
+
+impl_overflow_int!(u32, u64, u128, usize, i32, i64, i128);
+
+/// Returns a + b, following the overflow policy.
+#[inline]
+pub fn add<T: OverflowInt>(a: T, b: T) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        a.checked_add(b).unwrap_or_else(|| overflowed(a, "+", b))
+    } else {
+        a + b
+    }
+}
+
+/// Returns a * b, following the overflow policy.
+#[inline]
+pub fn mul<T: OverflowInt>(a: T, b: T) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        a.checked_mul(b).unwrap_or_else(|| overflowed(a, "*", b))
+    } else {
+        a * b
+    }
+}
+
+/// Returns base^exp, following the overflow policy.
+#[inline]
+pub fn pow<T: OverflowInt>(base: T, exp: u32) -> T {
+    if cfg!(feature = "checked-arithmetic") {
+        base.checked_pow(exp).unwrap_or_else(|| overflowed(base, "^", exp))
+    } else {
+        base.pow(exp)
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn overflowed<A: Display, B: Display>(a: A, op: &str, b: B) -> ! {
+    panic!("Arithmetic overflow: {} {} {}", a, op, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helpers_work() {
+        assert_eq!(add(2_u64, 3), 5);
+        assert_eq!(mul(4_u32, 5), 20);
+        assert_eq!(pow(10_u128, 30), 1_000_000_000_000_000_000_000_000_000_000);
+        assert_eq!(add(u64::MAX - 1, 1), u64::MAX);
+    }
+
+    // Without the feature, overflow is only caught when the build has overflow checks.
+    #[test]
+    #[cfg(any(feature = "checked-arithmetic", debug_assertions))]
+    #[should_panic]
+    fn overflow_panics_when_checked() {
+        mul(u64::MAX, 2);
+    }
+}
@@ -2,7 +2,8 @@ use utils::*;
 use fibonacci::FibonacciIterator;
 use prime::*;
 use calc;
-use std::convert::From;
+use digits;
+use std::convert::{From, TryFrom};
 
 pub fn p001() -> Option<u64> {
     let answer = (0..1000)
@@ -24,35 +25,53 @@ pub fn p002() -> Option<u64> {
 }
 
 pub fn p003() -> Option<u64> {
+    sub_execute(3, "a (prime list)   ", p003a);
+    sub_execute(3, "b (wheel factors)", p003b);
+    None
+}
+
+fn p003a() -> Option<u64> {
     let input = 600_851_475_143;
     let limit = calc::sqrt_ceil(input);
-    let primes = PrimeIterator::new()
-        .take_while(|&p| p < limit)
-        .collect::<Vec<u64>>();
+    let primes = cache::PRIMES.primes_below(limit);
 
     let answer = *primes.iter()
         .rev()
-        .skip_while(|&p| input % p != 0)
-        .next()
+        .find(|&p| input % p == 0)
         .unwrap();
 
     assert_eq!(answer, 6857);
     Some(answer)
 }
 
+fn p003b() -> Option<u64> {
+    // Trial division by wheel candidates divides out each factor as it is found, so it
+    // stops as soon as the remainder is prime rather than going all the way to sqrt(n).
+    let answer = factorize(600_851_475_143).factors().last().unwrap().0;
+
+    assert_eq!(answer, 6857);
+    Some(answer)
+}
+
 pub fn p004() -> Option<u64> {
+    sub_execute(4, "a (scan products)", p004a);
+    sub_execute(4, "b (palindromes)  ", p004b);
+    None
+}
+
+fn p004a() -> Option<u64> {
     let mut answer = 0;
 
     // Optimisations
-    // 1. Do not do the slow is_palindrome check if the product is not largest - this
-    //    means we do a simple cmp instead of a string format in most cases.
+    // 1. Do not do the is_palindrome check if the product is not largest - this
+    //    means we do a simple cmp instead of reversing the digits in most cases.
     // 2. We further reduce the number of is_palindrome checks by going backwards, so
     //    we start with the largest products, which are likely to be the largest palindromes.
 
     for a in (100..1000).rev() {
         for b in (100..1000).rev() {
             let product = a * b;
-            if product > answer && is_palindrome_number(product) {
+            if product > answer && digits::is_palindrome_in_base(product, 10) {
                 answer = product;
             }
         }
@@ -62,6 +81,23 @@ pub fn p004() -> Option<u64> {
     Some(answer)
 }
 
+fn p004b() -> Option<u64> {
+    // Turn it around: go down through the 6 digit palindromes, largest first, and stop at
+    // the first one with a 3 digit factor whose cofactor also has 3 digits. Checking
+    // the factors from the top means the cofactor only gets bigger, so we can stop once
+    // it goes past 999.
+    let has_3_digit_factors = |p: u64| (100..1000).rev()
+        .take_while(|&a| p / a < 1000)
+        .any(|a| p.is_multiple_of(a));
+
+    let answer = digits::palindromes(6, 10).rev()
+        .find(|&p| has_3_digit_factors(p))
+        .unwrap();
+
+    assert_eq!(answer, 906_609);
+    Some(answer)
+}
+
 pub fn p005() -> Option<u64> {
     sub_execute(5, "a", p005a);
     sub_execute(5, "b", p005b);
@@ -69,62 +105,19 @@ pub fn p005() -> Option<u64> {
 }
 
 fn p005a() -> Option<u64> {
-    // This problem is formally known as "lowest common multiple".
-
-    // must be even (* 2)
-    // must end in zero (* 10)
-    // must divide 20 (increments of 20)
-    // however, each candidate must also divide all 1..10, so increments are actually of 2520, not 20.
-
-    // This allows us to skip certain divisors as we know they are implicit
-    // from the fact that we are incrementing in 20s. 2520 is divisible by 20.
-    // eg div by 20 implies div by 10, 5, 4 and 2.
-    //    div by 18 implies div by 9, 6, 3 and 2
-    //    div by 14 implies div by 7
-    //    div by 8  implies div by 4 and 2
-    let divisors = [8, 11, 12, 13, 14, 15, 16, 17, 18, 19];
-    let divisible_by_all = |x| divisors.iter().all(|&n| x % n == 0);
-
-    let mut n = 2520;
-    let increment = 2520;
-    while !divisible_by_all(n) {
-        n += increment;
-    }
-
-    let answer = n;
+    // This problem is formally known as "lowest common multiple", and
+    // lcm(a, b, c) = lcm(lcm(a, b), c), so fold lcm over the numbers.
+    let answer = (1..21).fold(1, calc::lcm);
     assert_eq!(answer, 232_792_560);
     Some(answer)
 }
 
 fn p005b() -> Option<u64> {
-    // This problem is formally known as "lowest common multiple".
-
-    // Alternative solution. Must be divisible by a product of all the primes up to 20:
-    //     let increment = 2 * 3 * 5 * 7 * 11 * 13 * 17 * 19;
-    // This is also the starting number, not 2520.
-    // We then know that all candidate numbers will be divisible by all of those factors,
-    // so our list of divisors does not need to include them. Also, we can apply the filtering
-    // described in the previous solution, namely
-    //    div by 20 implies div by 10, 5, 4 and 2.
-    //    div by 18 implies div by 9, 6, 3 and 2
-    //    div by 14 implies div by 7
-    //    div by 8  implies div by 4 and 2
-    // p005a runs in 584.29 μs, and p005b runs in 1.43 μs.
-    let divisors = [14, 15, 16, 18, 20];
-    let divisible_by_all = |x| divisors.iter().all(|&n| x % n == 0);
-
-    let increment = 2 * 3 * 5 * 7 * 11 * 13 * 17 * 19;
-    let mut n = increment;
-    while !divisible_by_all(n) {
-        n += increment;
-    }
-
-    let answer = n;
+    // The same fold with checked_lcm, which stops with None rather than overflowing if the
+    // range were made longer. The LCM of 1 to 46 is the last that fits in a u64.
+    let answer = (1..21).try_fold(1_u64, calc::checked_lcm)?;
     assert_eq!(answer, 232_792_560);
     Some(answer)
-
-    // TODO: There are still faster ways of computing this.
-    // See https://projecteuler.net/thread=5;page=5
 }
 
 pub fn p006() -> Option<u64> {
@@ -137,7 +130,38 @@ pub fn p006() -> Option<u64> {
 }
 
 pub fn p007() -> Option<u64> {
-    let answer = PrimeIterator::new().nth(10000).unwrap();
+    // Benchmarks of the candidate wheels used by the prime iterator.
+    sub_execute(7, "a (+= 2)   ", p007a);
+    sub_execute(7, "b (mod 30) ", p007b);
+    sub_execute(7, "c (mod 210)", p007c);
+    sub_execute(7, "d (cache)  ", p007d);
+    None
+}
+
+fn p007a() -> Option<u64> {
+    let answer = PrimeIterator::with_wheel(&wheel::WHEEL_2).nth(10000).unwrap();
+
+    assert_eq!(answer, 104_743);
+    Some(answer)
+}
+
+fn p007b() -> Option<u64> {
+    let answer = PrimeIterator::with_wheel(&wheel::WHEEL_30).nth(10000).unwrap();
+
+    assert_eq!(answer, 104_743);
+    Some(answer)
+}
+
+fn p007c() -> Option<u64> {
+    let answer = PrimeIterator::with_wheel(&wheel::WHEEL_210).nth(10000).unwrap();
+
+    assert_eq!(answer, 104_743);
+    Some(answer)
+}
+
+fn p007d() -> Option<u64> {
+    // Free if an earlier problem has already filled the global prime cache.
+    let answer = cache::PRIMES.nth(10000);
 
     assert_eq!(answer, 104_743);
     Some(answer)
@@ -295,7 +319,15 @@ pub fn p009() -> Option<u64> {
 }
 
 pub fn p010() -> Option<u64> {
-    // TODO: Speed up my prime iterator.
+    sub_execute(10, "a", p010a);
+    sub_execute(10, "b", p010b);
+    sub_execute(10, "c", p010c);
+    sub_execute(10, "d", p010d);
+    None
+}
+
+fn p010a() -> Option<u64> {
+    // Sum every prime from the iterator.
     let answer = PrimeIterator::new()
         .take_while(|&p| p < 2_000_000)
         .sum();
@@ -303,3 +335,27 @@ pub fn p010() -> Option<u64> {
     assert_eq!(answer, 142_913_828_922);
     Some(answer)
 }
+
+fn p010b() -> Option<u64> {
+    // Lucy_Hedgehog's sub-linear algorithm never enumerates the primes at all.
+    let answer = u64::try_from(counting::prime_sum(1_999_999)).unwrap();
+
+    assert_eq!(answer, 142_913_828_922);
+    Some(answer)
+}
+
+fn p010c() -> Option<u64> {
+    // Sieve into the global prime cache, which later problems can reuse.
+    let answer = cache::PRIMES.primes_below(2_000_000).iter().sum();
+
+    assert_eq!(answer, 142_913_828_922);
+    Some(answer)
+}
+
+fn p010d() -> Option<u64> {
+    // A segmented sieve spread over all the CPUs.
+    let answer = u64::try_from(sieve::SegmentedSieve::new(2_000_000).sum()).unwrap();
+
+    assert_eq!(answer, 142_913_828_922);
+    Some(answer)
+}
@@ -1,7 +1,9 @@
 use utils::*;
 use matrix::Matrix;
+use prime::SpfSieve;
 use std::cmp::max;
 use calc;
+use digits;
 use std::str::FromStr;
 use std::iter::Iterator;
 use std::collections::{HashMap};
@@ -216,34 +218,50 @@ fn p011c() -> Option<u64> {
 }
 
 pub fn p012() -> Option<u64> {
+    sub_execute(12, "a (spf sieve)   ", p012a);
+    sub_execute(12, "b (num_divisors)", p012b);
+    None
+}
+
+fn p012a() -> Option<u64> {
+    // triangle(n) = n(n + 1) / 2, and n and n + 1 are coprime, so the number of divisors
+    // is the product of the number of divisors of each half. This means we only need to
+    // factorise numbers around n rather than around n^2.
+    let sieve = SpfSieve::new(100_000);
+    let num_divisors = |n| sieve.factorize(n).num_divisors();
+
     let mut answer = 0;
 
-    for n in 5..100_000_000 {
-        let tri = calc::triangle(n);
-        let divisors = calc::num_divisors(tri);
+    for n in 1..sieve.limit() {
+        // Exactly one of n and n + 1 is even, and that is the one which is halved.
+        #[allow(clippy::manual_div_ceil)]
+        let divisors = if n % 2 == 0 {
+            num_divisors(n / 2) * num_divisors(n + 1)
+        } else {
+            num_divisors(n) * num_divisors((n + 1) / 2)
+        };
+
         if divisors > 500 {
-            answer = tri;
+            answer = calc::triangle(n);
             break;
         }
-
-//        if divisors.len() > max_divisors {
-//            max_divisors = divisors.len();
-//            answer = tri;
-//        }
-//
-//        if max_divisors > 500 {
-//            break;
-//        }
-//
-//        if n % 100 == 0 {
-//            println!("n = {}, tri = {}, max divisors = {}", n, tri, max_divisors);
-//        }
     }
 
     assert_eq!(answer, 76_576_500);
     Some(answer)
 }
 
+fn p012b() -> Option<u64> {
+    // Factorise each triangle number directly, using primes from the global cache.
+    let answer = (1..)
+        .map(calc::triangle)
+        .find(|&tri| calc::num_divisors(tri) > 500)
+        .unwrap();
+
+    assert_eq!(answer, 76_576_500);
+    Some(answer)
+}
+
 pub fn p013() -> Option<u64> {
     let input = "37107287533902102798797998220837590246510135740250
 46376937677490009712648124896970078050417018260538
@@ -372,11 +390,11 @@ pub fn p013() -> Option<u64> {
 
     for col in (0..column_len).rev() {
         let mut column_sum = 0;
-        for row in 0..input_len {
-            column_sum += input[row][col];
+        for row in &input {
+            column_sum += row[col];
         }
 
-        let mut d = column_sum;
+        let d = column_sum;
         let digit1 = d / 100;
         let d = d - digit1 * 100;
         let digit2 = d / 10;
@@ -407,11 +425,9 @@ pub fn p013() -> Option<u64> {
         }
     }
 
-    let answer = calc::vec_to_num(&answer.into_iter()
+    let answer = digits::from_digits(answer.into_iter()
         .skip_while(|&n| n == 0)
-        .take(10)
-        .map(u64::from)
-        .collect::<Vec<_>>());
+        .take(10), 10);
 
     assert_eq!(answer, 5_537_376_230);
     Some(answer)
@@ -430,7 +446,7 @@ pub fn p014a() -> Option<u64> {
     let mut answer_n = 0;
 
     for n in 2..1_000_000 {
-        let clen = calc::collatz_len_simple(n);
+        let clen = calc::collatz::collatz_len_simple(n);
 
         if clen > answer_len {
             answer_len = clen;
@@ -440,18 +456,17 @@ pub fn p014a() -> Option<u64> {
     }
 
     assert_eq!(answer_n, 837_799);
-    Some(answer_n as u64)
+    Some(answer_n)
 }
 
 pub fn p014b() -> Option<u64> {
     let mut known_collatzes = HashMap::<u32, u32>::new();
-    known_collatzes.insert(1, 1);
 
     let mut answer_len = 0;
     let mut answer_n = 0;
 
     for n in 2..1_000_000 {
-        let clen = calc::collatz_len(n, &mut known_collatzes);
+        let clen = calc::collatz::collatz_len(n, &mut known_collatzes);
 
         if clen > answer_len {
             answer_len = clen;
@@ -461,17 +476,17 @@ pub fn p014b() -> Option<u64> {
     }
 
     assert_eq!(answer_n, 837_799);
-    Some(u64::from(answer_n))
+    Some(answer_n)
 }
 
 pub fn p014c() -> Option<u64> {
-    let mut known_collatzes = calc::KnownCollatzes::new();
+    let mut known_collatzes = calc::collatz::KnownCollatzes::new();
 
     let mut answer_len = 0;
     let mut answer_n = 0;
 
     for n in 2..1_000_000 {
-        let clen = calc::collatz_len2(n, &mut known_collatzes);
+        let clen = calc::collatz::collatz_len(n, &mut known_collatzes);
 
         if clen > answer_len {
             answer_len = clen;
@@ -481,18 +496,17 @@ pub fn p014c() -> Option<u64> {
     }
 
     assert_eq!(answer_n, 837_799);
-    Some(answer_n as u64)
+    Some(answer_n)
 }
 
 pub fn p014d() -> Option<u64> {
-    let mut known_collatzes = FnvHashMap::default();
-    known_collatzes.insert(1, 1);
+    let mut known_collatzes = FnvHashMap::<usize, usize>::default();
 
     let mut answer_len = 0;
     let mut answer_n = 0;
 
     for n in 2..1_000_000 {
-        let clen = calc::collatz_len3(n, &mut known_collatzes);
+        let clen = calc::collatz::collatz_len(n, &mut known_collatzes);
 
         if clen > answer_len {
             answer_len = clen;
@@ -502,5 +516,5 @@ pub fn p014d() -> Option<u64> {
     }
 
     assert_eq!(answer_n, 837_799);
-    Some(answer_n as u64)
+    Some(answer_n)
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+use std::collections::VecDeque;
+use prime::is_prime;
+
+// Statistics about the distribution of primes. Everything here works on a stream of
+// primes, such as a `PrimeIterator` or `PRIMES.iter()`, and only keeps a small window of
+// them, so there is no need to store all the primes up to the point of interest. Bear in
+// mind that `PRIMES.iter()` does store them, in the global cache.
+
+/// A gap between consecutive primes: the next prime after `start` is `start + gap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub start: u64,
+    pub gap: u64
+}
+
+/// Consecutive primes in a pattern: the primes are `start + offset` for each offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimeTuple {
+    pub start: u64,
+    pub offsets: &'static [u64]
+}
+
+/// A run of `len` consecutive primes beginning at `start` which add up to `sum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimeRun {
+    pub start: u64,
+    pub len: usize,
+    pub sum: u64
+}
+
+/// The two patterns of prime triplets, (p, p + 2, p + 6) and (p, p + 4, p + 6).
+pub const TRIPLETS: &[&[u64]] = &[&[0, 2, 6], &[0, 4, 6]];
+
+/// The pattern of prime quadruplets, (p, p + 2, p + 6, p + 8).
+pub const QUADRUPLETS: &[&[u64]] = &[&[0, 2, 6, 8]];
+
+/// An iterator over the gaps between consecutive primes.
+/// The values start: (2, 1), (3, 2), (5, 2), (7, 4), (11, 2), ...
+pub struct Gaps<I> {
+    primes: I,
+    prev: Option<u64>
+}
+
+pub fn gaps<I: Iterator<Item = u64>>(primes: I) -> Gaps<I> {
+    Gaps { primes, prev: None }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for Gaps<I> {
+    type Item = Gap;
+
+    fn next(&mut self) -> Option<Gap> {
+        if self.prev.is_none() {
+            self.prev = self.primes.next();
+        }
+
+        let start = self.prev?;
+        let next = self.primes.next()?;
+        self.prev = Some(next);
+        Some(Gap { start, gap: next - start })
+    }
+}
+
+/// The maximal gaps, which are the gaps larger than every gap before them.
+/// The values start: (2, 1), (3, 2), (7, 4), (23, 6), (89, 8), (113, 14), ...
+pub fn maximal_gaps<I: Iterator<Item = u64>>(primes: I) -> impl Iterator<Item = Gap> {
+    let mut largest = 0;
+    gaps(primes).filter(move |g| {
+        if g.gap > largest {
+            largest = g.gap;
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns the first gap of exactly `gap` between consecutive primes from `primes`, such as
+/// `PrimeIterator::new()`, or None for 0 and odd gaps other than 1, which never occur. Every
+/// even gap is conjectured to occur, so for those this reads primes until it finds one, or
+/// until `primes` runs out.
+pub fn first_gap_of<I>(gap: u64, primes: I) -> Option<Gap>
+    where I: IntoIterator<Item = u64>
+{
+    if gap == 0 || (gap != 1 && !gap.is_multiple_of(2)) {
+        return None;
+    }
+    gaps(primes.into_iter()).find(|g| g.gap == gap)
+}
+
+/// An iterator over runs of consecutive primes which match one of the `patterns`, such
+/// as `TRIPLETS` or `QUADRUPLETS`. All the patterns must be the same length and start at 0.
+pub struct PrimeTuples<I> {
+    primes: I,
+    patterns: &'static [&'static [u64]],
+    window: VecDeque<u64>
+}
+
+pub fn prime_tuples<I>(primes: I, patterns: &'static [&'static [u64]]) -> PrimeTuples<I>
+    where I: Iterator<Item = u64>
+{
+    assert!(!patterns.is_empty() && patterns.iter().all(|p| p.len() == patterns[0].len() && p[0] == 0));
+    PrimeTuples { primes, patterns, window: VecDeque::with_capacity(patterns[0].len()) }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for PrimeTuples<I> {
+    type Item = PrimeTuple;
+
+    fn next(&mut self) -> Option<PrimeTuple> {
+        let k = self.patterns[0].len();
+
+        loop {
+            if self.window.len() == k {
+                self.window.pop_front();
+            }
+            while self.window.len() < k {
+                self.window.push_back(self.primes.next()?);
+            }
+
+            let start = self.window[0];
+            let window = &self.window;
+            let matching = self.patterns.iter()
+                .find(|pattern| pattern.iter().zip(window).all(|(&offset, &p)| p - start == offset));
+
+            if let Some(&offsets) = matching {
+                return Some(PrimeTuple { start, offsets });
+            }
+        }
+    }
+}
+
+/// Finds the longest run of consecutive primes whose sum is a prime less than limit
+/// (Project Euler 50). If there are several runs of the same length the one with the
+/// smallest sum is returned. `primes` must yield the primes in order starting from 2, at
+/// least up to limit, such as `PrimeIterator::new()`. They are only read as far as the runs
+/// being checked need, which is well short of limit for any large limit.
+pub fn longest_consecutive_prime_sum<I>(limit: u64, primes: I) -> Option<PrimeRun>
+    where I: IntoIterator<Item = u64>
+{
+    let mut source = primes.into_iter();
+    let mut primes = Vec::new();
+    // prefix[i] is the sum of the first i primes.
+    let mut prefix = vec![0];
+
+    // No run can be longer than the run starting at 2 with a sum below the limit. A run
+    // which needs a prime past the end of the source would include a prime of at least
+    // limit, so its sum would be too big anyway.
+    for p in source.by_ref() {
+        let sum = prefix[primes.len()] + p;
+        primes.push(p);
+        prefix.push(sum);
+        if sum >= limit {
+            break;
+        }
+    }
+    let mut max_len = primes.len();
+    if max_len > 0 && prefix[max_len] >= limit {
+        max_len -= 1;
+    }
+
+    for len in (1..max_len + 1).rev() {
+        for start in 0.. {
+            while primes.len() < start + len {
+                let p = match source.next() {
+                    Some(p) => p,
+                    None => break
+                };
+                let sum = prefix[primes.len()] + p;
+                primes.push(p);
+                prefix.push(sum);
+            }
+
+            if primes.len() < start + len {
+                break;
+            }
+            let sum = prefix[start + len] - prefix[start];
+            if sum >= limit {
+                break;
+            }
+            if is_prime(sum) {
+                return Some(PrimeRun { start: primes[start], len, sum });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prime::PrimeIterator;
+    use prime::cache::PRIMES;
+
+    #[test]
+    fn gaps_works() {
+        let g = gaps(PrimeIterator::new()).take(5).map(|g| (g.start, g.gap)).collect::<Vec<_>>();
+        assert_eq!(g, vec![(2, 1), (3, 2), (5, 2), (7, 4), (11, 2)]);
+        assert_eq!(gaps(vec![2].into_iter()).next(), None);
+        assert_eq!(gaps(Vec::new().into_iter()).next(), None);
+    }
+
+    #[test]
+    fn maximal_gaps_works() {
+        // OEIS A002386 and A005250.
+        let g = maximal_gaps(PRIMES.iter()).take(14).map(|g| (g.start, g.gap)).collect::<Vec<_>>();
+        assert_eq!(g, vec![(2, 1), (3, 2), (7, 4), (23, 6), (89, 8), (113, 14), (523, 18), (887, 20),
+                           (1129, 22), (1327, 34), (9551, 36), (15_683, 44), (19_609, 52), (31_397, 72)]);
+    }
+
+    #[test]
+    fn first_gap_of_works() {
+        // OEIS A000230.
+        assert_eq!(first_gap_of(1, PrimeIterator::new()), Some(Gap { start: 2, gap: 1 }));
+        assert_eq!(first_gap_of(2, PrimeIterator::new()), Some(Gap { start: 3, gap: 2 }));
+        assert_eq!(first_gap_of(6, PrimeIterator::new()), Some(Gap { start: 23, gap: 6 }));
+        assert_eq!(first_gap_of(10, PrimeIterator::new()), Some(Gap { start: 139, gap: 10 }));
+        assert_eq!(first_gap_of(12, PrimeIterator::new()), Some(Gap { start: 199, gap: 12 }));
+        assert_eq!(first_gap_of(100, PrimeIterator::new()), Some(Gap { start: 396_733, gap: 100 }));
+        assert_eq!(first_gap_of(0, PrimeIterator::new()), None);
+        assert_eq!(first_gap_of(3, PrimeIterator::new()), None);
+        assert_eq!(first_gap_of(101, PrimeIterator::new()), None);
+        assert_eq!(first_gap_of(100, PRIMES.iter()), Some(Gap { start: 396_733, gap: 100 }));
+        assert_eq!(first_gap_of(8, vec![2, 3, 5, 7, 11]), None);
+    }
+
+    #[test]
+    fn triplets_works() {
+        // OEIS A007529.
+        let starts = prime_tuples(PRIMES.iter(), TRIPLETS).take(15).map(|t| t.start).collect::<Vec<_>>();
+        assert_eq!(starts, vec![5, 7, 11, 13, 17, 37, 41, 67, 97, 101, 103, 107, 191, 193, 223]);
+
+        let first_two = prime_tuples(PRIMES.iter(), TRIPLETS).take(2).collect::<Vec<_>>();
+        assert_eq!(first_two[0].offsets, &[0, 2, 6]);
+        assert_eq!(first_two[1].offsets, &[0, 4, 6]);
+    }
+
+    #[test]
+    fn quadruplets_works() {
+        // OEIS A007530.
+        let starts = prime_tuples(PRIMES.iter(), QUADRUPLETS).take(10).map(|t| t.start).collect::<Vec<_>>();
+        assert_eq!(starts, vec![5, 11, 101, 191, 821, 1481, 1871, 2081, 3251, 3461]);
+    }
+
+    #[test]
+    fn longest_consecutive_prime_sum_works() {
+        assert_eq!(longest_consecutive_prime_sum(2, PrimeIterator::new()), None);
+        assert_eq!(longest_consecutive_prime_sum(3, PrimeIterator::new()), Some(PrimeRun { start: 2, len: 1, sum: 2 }));
+        // 2 + 3 = 5 is too big, and ties between single primes go to the smallest.
+        assert_eq!(longest_consecutive_prime_sum(5, PrimeIterator::new()), Some(PrimeRun { start: 2, len: 1, sum: 2 }));
+        // The examples from Project Euler 50, and the answer.
+        assert_eq!(longest_consecutive_prime_sum(100, PrimeIterator::new()), Some(PrimeRun { start: 2, len: 6, sum: 41 }));
+        assert_eq!(longest_consecutive_prime_sum(1000, PrimeIterator::new()), Some(PrimeRun { start: 7, len: 21, sum: 953 }));
+        assert_eq!(longest_consecutive_prime_sum(1_000_000, PrimeIterator::new()), Some(PrimeRun { start: 7, len: 543, sum: 997_651 }));
+        assert_eq!(longest_consecutive_prime_sum(1_000_000, PRIMES.iter()), Some(PrimeRun { start: 7, len: 543, sum: 997_651 }));
+        // A source which stops at the limit is enough.
+        let below_100 = PrimeIterator::new().take_while(|&p| p < 100).collect::<Vec<_>>();
+        assert_eq!(longest_consecutive_prime_sum(100, below_100), Some(PrimeRun { start: 2, len: 6, sum: 41 }));
+        assert_eq!(longest_consecutive_prime_sum(0, Vec::new()), None);
+        assert_eq!(longest_consecutive_prime_sum(3, vec![2]), Some(PrimeRun { start: 2, len: 1, sum: 2 }));
+    }
+}
@@ -0,0 +1,200 @@
+use overflow::{self, OverflowInt};
+
+// Tables of the classic arithmetic functions for every n in 0..=limit. Each table is
+// filled by a linear sieve: every composite m is visited exactly once, as p * i where
+// p is the smallest prime factor of m, and the value for m is derived from the value
+// for i. The entry for 0 is always 0, and the entry for 1 is always 1.
+
+/// Returns Euler's totient φ(n) for every n in 0..=limit.
+pub fn totients(limit: usize) -> Vec<u64> {
+    let mut phi = vec![0_u64; limit + 1];
+    let mut primes = Vec::new();
+    if limit >= 1 {
+        phi[1] = 1;
+    }
+
+    for i in 2..limit + 1 {
+        if phi[i] == 0 {
+            phi[i] = i as u64 - 1;
+            primes.push(i);
+        }
+
+        // Comparing p with limit / i rather than p * i with limit cannot overflow.
+        for &p in primes.iter().take_while(|&&p| p <= limit / i) {
+            let m = p * i;
+            if i % p == 0 {
+                phi[m] = overflow::mul(phi[i], p as u64);
+                break;
+            }
+            phi[m] = overflow::mul(phi[i], p as u64 - 1);
+        }
+    }
+
+    phi
+}
+
+/// Returns the Möbius function μ(n) for every n in 0..=limit.
+pub fn mobius(limit: usize) -> Vec<i8> {
+    let mut mu = vec![0_i8; limit + 1];
+    let mut is_composite = vec![false; limit + 1];
+    let mut primes = Vec::new();
+    if limit >= 1 {
+        mu[1] = 1;
+    }
+
+    for i in 2..limit + 1 {
+        if !is_composite[i] {
+            mu[i] = -1;
+            primes.push(i);
+        }
+
+        // Comparing p with limit / i rather than p * i with limit cannot overflow.
+        for &p in primes.iter().take_while(|&&p| p <= limit / i) {
+            let m = p * i;
+            is_composite[m] = true;
+            if i % p == 0 {
+                mu[m] = 0;
+                break;
+            }
+            mu[m] = -mu[i];
+        }
+    }
+
+    mu
+}
+
+/// Returns the number of divisors d(n), also written τ(n), for every n in 0..=limit.
+pub fn divisor_counts(limit: usize) -> Vec<u32> {
+    let mut d = vec![0_u32; limit + 1];
+    // The exponent of the smallest prime factor of n.
+    let mut exponent = vec![0_u32; limit + 1];
+    let mut primes = Vec::new();
+    if limit >= 1 {
+        d[1] = 1;
+    }
+
+    for i in 2..limit + 1 {
+        if d[i] == 0 {
+            d[i] = 2;
+            exponent[i] = 1;
+            primes.push(i);
+        }
+
+        // Comparing p with limit / i rather than p * i with limit cannot overflow.
+        for &p in primes.iter().take_while(|&&p| p <= limit / i) {
+            let m = p * i;
+            if i % p == 0 {
+                exponent[m] = exponent[i] + 1;
+                d[m] = overflow::mul(d[i] / (exponent[i] + 1), exponent[m] + 1);
+                break;
+            }
+            exponent[m] = 1;
+            d[m] = overflow::mul(d[i], 2);
+        }
+    }
+
+    d
+}
+
+/// Returns the sum of divisors σ(n) for every n in 0..=limit.
+pub fn divisor_sums(limit: usize) -> Vec<u64> {
+    let mut sigma = vec![0_u64; limit + 1];
+    // 1 + p + p^2 + ... + p^e, where p^e is the largest power of the smallest prime
+    // factor of n that divides n.
+    let mut power_sum = vec![0_u64; limit + 1];
+    let mut primes = Vec::new();
+    if limit >= 1 {
+        sigma[1] = 1;
+    }
+
+    for i in 2..limit + 1 {
+        if sigma[i] == 0 {
+            sigma[i] = i as u64 + 1;
+            power_sum[i] = i as u64 + 1;
+            primes.push(i);
+        }
+
+        // Comparing p with limit / i rather than p * i with limit cannot overflow.
+        for &p in primes.iter().take_while(|&&p| p <= limit / i) {
+            let m = p * i;
+            if i % p == 0 {
+                power_sum[m] = overflow::add(overflow::mul(power_sum[i], p as u64), 1);
+                sigma[m] = overflow::mul(sigma[i] / power_sum[i], power_sum[m]);
+                break;
+            }
+            power_sum[m] = p as u64 + 1;
+            sigma[m] = overflow::mul(sigma[i], p as u64 + 1);
+        }
+    }
+
+    sigma
+}
+
+/// Returns the running totals of a table, so that `result[n]` is the sum of
+/// `values[0..=n]`. The sum type can be wider than the element type, for example
+/// `prefix_sums::<i8, i64>(&mobius(n))` gives the Mertens function.
+pub fn prefix_sums<T, S>(values: &[T]) -> Vec<S>
+    where T: Copy + Into<S>,
+          S: OverflowInt + Default
+{
+    let mut total = S::default();
+    values.iter()
+        .map(|&v| {
+            total = overflow::add(total, v.into());
+            total
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc;
+
+    #[test]
+    fn totients_works() {
+        assert_eq!(totients(0), vec![0]);
+        assert_eq!(totients(12), vec![0, 1, 1, 2, 2, 4, 2, 6, 4, 6, 4, 10, 4]);
+
+        let phi = totients(500);
+        for n in 1..501 {
+            let expected = (1..n + 1).filter(|&k| calc::gcd(n, k) == 1).count() as u64;
+            assert_eq!(phi[n as usize], expected, "φ({})", n);
+        }
+    }
+
+    #[test]
+    fn mobius_works() {
+        assert_eq!(mobius(12), vec![0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0]);
+        assert_eq!(mobius(30)[30], -1);
+        assert_eq!(mobius(210)[210], 1);
+    }
+
+    #[test]
+    fn divisor_counts_works() {
+        assert_eq!(divisor_counts(12), vec![0, 1, 2, 2, 3, 2, 4, 2, 4, 3, 4, 2, 6]);
+
+        let d = divisor_counts(1000);
+        for n in 1..1001 {
+            assert_eq!(u64::from(d[n as usize]), calc::divisors(n).len() as u64, "d({})", n);
+        }
+    }
+
+    #[test]
+    fn divisor_sums_works() {
+        assert_eq!(divisor_sums(12), vec![0, 1, 3, 4, 7, 6, 12, 8, 15, 13, 18, 12, 28]);
+
+        let sigma = divisor_sums(1000);
+        for n in 1..1001 {
+            assert_eq!(sigma[n as usize], calc::divisors(n).iter().sum::<u64>(), "σ({})", n);
+        }
+    }
+
+    #[test]
+    fn prefix_sums_works() {
+        // Σφ(n) for n <= 10 is 32, and the Mertens function M(10) is -1.
+        assert_eq!(prefix_sums::<u64, u64>(&totients(10))[10], 32);
+        assert_eq!(prefix_sums::<i8, i64>(&mobius(10)), vec![0, 1, 0, -1, -1, -2, -1, -2, -2, -2, -1]);
+        assert_eq!(prefix_sums::<u32, u64>(&[]), vec![]);
+    }
+}
@@ -0,0 +1,375 @@
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use prime::disk;
+use prime::sieve::Segment;
+
+/// The process-wide prime cache. Every problem that needs a list of primes should take it
+/// from here, so that running several problems only computes each prime once.
+pub static PRIMES: PrimeCache = PrimeCache::new();
+
+/// A thread-safe table of primes which grows on demand.
+///
+/// Readers take a cheap snapshot (an `Arc` of the table) so they never block each other or
+/// hold a lock while they work. When a larger bound is requested the table is extended with
+/// a segmented sieve; only one thread grows the table at a time, and the new table is
+/// swapped in once it is complete.
+///
+/// The cache can optionally be backed by a file (see `enable_disk_cache`), in which case it
+/// is loaded from the file the first time it is needed, and saved back to it whenever it
+/// grows.
+pub struct PrimeCache {
+    table: RwLock<Option<Arc<Table>>>,
+    grow_lock: Mutex<()>,
+    disk_path: Mutex<Option<PathBuf>>,
+    // Whether the file at disk_path has been read yet. Only changed with the grow lock held.
+    disk_loaded: AtomicBool
+}
+
+/// All primes less than `limit`, in ascending order.
+struct Table {
+    primes: Vec<u64>,
+    limit: u64
+}
+
+impl PrimeCache {
+    pub const fn new() -> Self {
+        PrimeCache {
+            table: RwLock::new(None),
+            grow_lock: Mutex::new(()),
+            disk_path: Mutex::new(None),
+            disk_loaded: AtomicBool::new(false)
+        }
+    }
+
+    /// Persists the cache to a file called `primes.bin` in dir, which is created if
+    /// necessary. Any primes already in the file are used instead of being recomputed.
+    pub fn enable_disk_cache(&self, dir: PathBuf) {
+        let _guard = self.grow_lock.lock().unwrap();
+        *self.disk_path.lock().unwrap() = Some(dir.join("primes.bin"));
+        self.disk_loaded.store(false, Ordering::Release);
+    }
+
+    pub fn disable_disk_cache(&self) {
+        *self.disk_path.lock().unwrap() = None;
+    }
+
+    /// Returns all the primes less than limit.
+    pub fn primes_below(&self, limit: u64) -> Primes {
+        let table = self.table_with_limit(limit);
+        let len = match table.primes.binary_search(&limit) {
+            Ok(i) | Err(i) => i
+        };
+        Primes { table, len }
+    }
+
+    /// Returns the n'th prime, counting from 0 like `Iterator::nth`, so `nth(0)` is 2.
+    /// Panics if there are not that many primes in a u64.
+    pub fn nth(&self, n: usize) -> u64 {
+        let mut table = self.snapshot();
+        while table.primes.len() <= n {
+            assert!(table.limit < u64::MAX, "There are fewer than {} primes in a u64", n + 1);
+            table = self.table_with_limit(table.limit.saturating_mul(2));
+        }
+        table.primes[n]
+    }
+
+    /// Returns an iterator over all the primes, starting with 2. It yields primes from the
+    /// cache for as long as it can, and grows the cache when it runs out. It ends after the
+    /// largest u64 prime.
+    pub fn iter<'a>(&'a self) -> CachedPrimeIterator<'a> {
+        CachedPrimeIterator { cache: self, table: self.snapshot(), index: 0 }
+    }
+
+    /// If a disk cache is enabled, returns all the primes the cache has without sieving any
+    /// more. The file is only read the first time, so after that this does not block or do
+    /// any I/O. Returns None if there is no disk cache.
+    pub fn stored_primes(&self) -> Option<Primes> {
+        let disk_path = self.disk_path.lock().unwrap().clone()?;
+        if !self.disk_loaded.load(Ordering::Acquire) {
+            let _guard = self.grow_lock.lock().unwrap();
+            self.load_from_disk_once(&disk_path, self.snapshot());
+        }
+
+        let table = self.snapshot();
+        let len = table.primes.len();
+        Some(Primes { table, len })
+    }
+
+    /// Adds primes which were found some other way, such as by a `PrimeIterator`. `primes`
+    /// must be all the primes less than limit, in order. Nothing happens unless they go
+    /// further than the cache already does, in which case they replace it and are saved to
+    /// the disk cache if there is one.
+    pub fn extend_with(&self, primes: Vec<u64>, limit: u64) {
+        let _guard = self.grow_lock.lock().unwrap();
+        if self.snapshot().limit >= limit {
+            return;
+        }
+
+        if let Some(ref path) = *self.disk_path.lock().unwrap() {
+            // The cache is only an optimisation, so failing to write it is not fatal.
+            let _ = disk::save(path, limit, &primes);
+        }
+        self.publish(Table { primes, limit });
+    }
+
+    /// Returns the exclusive upper bound of the primes currently in the cache.
+    pub fn limit(&self) -> u64 {
+        self.snapshot().limit
+    }
+
+    fn snapshot(&self) -> Arc<Table> {
+        let table = self.table.read().unwrap();
+        match *table {
+            Some(ref t) => Arc::clone(t),
+            None => Arc::new(Table { primes: Vec::new(), limit: 2 })
+        }
+    }
+
+    /// Returns a table containing at least all the primes less than limit, growing the
+    /// cache if necessary.
+    fn table_with_limit(&self, limit: u64) -> Arc<Table> {
+        let table = self.snapshot();
+        if table.limit >= limit {
+            return table;
+        }
+
+        let _guard = self.grow_lock.lock().unwrap();
+
+        // Another thread may have grown the table while we were waiting for the lock.
+        let mut table = self.snapshot();
+        if table.limit >= limit {
+            return table;
+        }
+
+        let disk_path = self.disk_path.lock().unwrap().clone();
+        if let Some(ref path) = disk_path {
+            table = self.load_from_disk_once(path, table);
+            if table.limit >= limit {
+                return table;
+            }
+        }
+
+        // At least double each time so that repeated small requests don't each copy the table.
+        let new_limit = limit.max(table.limit.saturating_mul(2));
+        let mut primes = Vec::with_capacity(table.primes.len().saturating_mul(2));
+        primes.extend_from_slice(&table.primes);
+        let mut low = table.limit;
+        while low < new_limit {
+            // The primes we already have can sieve everything up to low^2.
+            let high = new_limit.min(low.saturating_mul(low));
+            let segment = Segment::new(low, high, &primes);
+            primes.extend(segment.primes());
+            low = high;
+        }
+
+        if let Some(ref path) = disk_path {
+            // The cache is only an optimisation, so failing to write it is not fatal.
+            let _ = disk::save(path, new_limit, &primes);
+        }
+
+        self.publish(Table { primes, limit: new_limit })
+    }
+
+    /// Replaces table with the one in the file at path if that has more primes, unless the
+    /// file has already been read. The caller must hold the grow lock.
+    fn load_from_disk_once(&self, path: &Path, table: Arc<Table>) -> Arc<Table> {
+        if self.disk_loaded.swap(true, Ordering::AcqRel) {
+            return table;
+        }
+
+        // A missing or corrupt file is not an error, we just sieve the primes instead.
+        if disk::read_limit(path).map(|l| l > table.limit).unwrap_or(false) {
+            if let Ok((limit, primes)) = disk::load(path) {
+                return self.publish(Table { primes, limit });
+            }
+        }
+        table
+    }
+
+    fn publish(&self, table: Table) -> Arc<Table> {
+        let table = Arc::new(table);
+        *self.table.write().unwrap() = Some(Arc::clone(&table));
+        table
+    }
+}
+
+/// A read-only view of a prefix of the prime cache. It dereferences to a `&[u64]`.
+#[derive(Clone)]
+pub struct Primes {
+    table: Arc<Table>,
+    len: usize
+}
+
+impl Deref for Primes {
+    type Target = [u64];
+
+    fn deref(&self) -> &[u64] {
+        &self.table.primes[..self.len]
+    }
+}
+
+/// An iterator over the primes which resumes from the cache.
+pub struct CachedPrimeIterator<'a> {
+    cache: &'a PrimeCache,
+    table: Arc<Table>,
+    index: usize
+}
+
+impl<'a> Iterator for CachedPrimeIterator<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.index == self.table.primes.len() {
+            // A table up to u64::MAX already has every u64 prime.
+            if self.table.limit == u64::MAX {
+                return None;
+            }
+            self.table = self.cache.table_with_limit(self.table.limit.saturating_mul(2));
+        }
+
+        self.index += 1;
+        Some(self.table.primes[self.index - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, thread};
+    use prime::PrimeIterator;
+    use prime::wheel::WHEEL_30;
+
+    #[test]
+    fn primes_below_works() {
+        let cache = PrimeCache::new();
+        assert_eq!(&*cache.primes_below(0), &[]);
+        assert_eq!(&*cache.primes_below(2), &[]);
+        assert_eq!(&*cache.primes_below(3), &[2]);
+        assert_eq!(&*cache.primes_below(20), &[2, 3, 5, 7, 11, 13, 17, 19]);
+        // Once the cache has grown, smaller requests are served from it.
+        assert!(cache.limit() >= 20);
+        assert_eq!(&*cache.primes_below(8), &[2, 3, 5, 7]);
+        assert_eq!(&*cache.primes_below(7), &[2, 3, 5]);
+
+        let expected = PrimeIterator::new().take_while(|&p| p < 100_000).collect::<Vec<_>>();
+        assert_eq!(&*cache.primes_below(100_000), &expected[..]);
+    }
+
+    #[test]
+    fn nth_works() {
+        let cache = PrimeCache::new();
+        assert_eq!(cache.nth(0), 2);
+        assert_eq!(cache.nth(3), 7);
+        assert_eq!(cache.nth(10_000), 104_743);
+    }
+
+    #[test]
+    fn iter_works() {
+        let cache = PrimeCache::new();
+        let expected = PrimeIterator::new().take(5000).collect::<Vec<_>>();
+        assert_eq!(cache.iter().take(5000).collect::<Vec<_>>(), expected);
+        // A second iterator resumes from what the first one computed.
+        let limit = cache.limit();
+        assert_eq!(cache.iter().take(5000).collect::<Vec<_>>(), expected);
+        assert_eq!(cache.limit(), limit);
+    }
+
+    #[test]
+    fn iter_ends_after_the_last_u64_prime() {
+        // Sieving that far is out of the question, so pretend it has been done.
+        let cache = PrimeCache::new();
+        cache.extend_with(vec![2, 3, 5], u64::MAX);
+        assert_eq!(cache.iter().collect::<Vec<_>>(), vec![2, 3, 5]);
+        assert_eq!(cache.nth(2), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nth_panics_past_the_last_u64_prime() {
+        let cache = PrimeCache::new();
+        cache.extend_with(vec![2, 3, 5], u64::MAX);
+        cache.nth(3);
+    }
+
+    #[test]
+    fn disk_cache_is_loaded_and_extended() {
+        let dir = disk::test_dir("disk_cache_is_loaded_and_extended");
+        let _ = fs::remove_dir_all(&dir);
+
+        let cache = PrimeCache::new();
+        cache.enable_disk_cache(dir.clone());
+        let expected = PrimeIterator::new().take_while(|&p| p < 50_000).collect::<Vec<_>>();
+        assert_eq!(&*cache.primes_below(10_000), &expected[..1229]);
+        let saved_limit = cache.limit();
+        assert_eq!(disk::read_limit(&dir.join("primes.bin")).unwrap(), saved_limit);
+
+        // A new cache picks up where the old one left off...
+        let cache = PrimeCache::new();
+        cache.enable_disk_cache(dir.clone());
+        assert_eq!(&*cache.primes_below(100), &expected[..25]);
+        assert_eq!(cache.limit(), saved_limit);
+
+        // ...and extends the file when a larger bound is requested.
+        assert_eq!(&*cache.primes_below(50_000), &expected[..]);
+        assert!(disk::read_limit(&dir.join("primes.bin")).unwrap() >= 50_000);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn prime_iterator_starts_from_the_disk_cache() {
+        // PrimeIterator needs a cache which lives forever, so each step gets its own static.
+        static WRITER: PrimeCache = PrimeCache::new();
+        static READER: PrimeCache = PrimeCache::new();
+        static EXTENDER: PrimeCache = PrimeCache::new();
+
+        let dir = disk::test_dir("prime_iterator_starts_from_the_disk_cache");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("primes.bin");
+        let expected = PrimeIterator::new().take(20_000).collect::<Vec<_>>();
+
+        assert!(WRITER.stored_primes().is_none());
+        WRITER.enable_disk_cache(dir.clone());
+        assert_eq!(WRITER.primes_below(100_000).len(), 9592);
+
+        // A new cache has nothing in memory, so the primes come from the file, which is
+        // only read once.
+        READER.enable_disk_cache(dir.clone());
+        assert_eq!(READER.stored_primes().unwrap().len(), 9592);
+        assert!(READER.limit() >= 100_000);
+        assert_eq!(READER.stored_primes().unwrap().len(), 9592);
+
+        // The iterator yields the stored primes and then carries on past them...
+        let primes = PrimeIterator::<u64>::with_cache(&WHEEL_30, &READER).take(20_000).collect::<Vec<_>>();
+        assert_eq!(primes, expected);
+        let primes = PrimeIterator::<u32>::with_cache(&WHEEL_30, &READER).take(20_000).collect::<Vec<_>>();
+        assert!(primes.iter().zip(&expected).all(|(&p, &q)| u64::from(p) == q));
+
+        // ...adding the primes it found to the cache and the file.
+        assert_eq!(READER.limit(), expected[19_999] + 1);
+        assert_eq!(disk::read_limit(&path).unwrap(), expected[19_999] + 1);
+        EXTENDER.enable_disk_cache(dir.clone());
+        assert_eq!(&*EXTENDER.stored_primes().unwrap(), &expected[..]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_can_be_shared_between_threads() {
+        static CACHE: PrimeCache = PrimeCache::new();
+        let expected = PrimeIterator::new().take_while(|&p| p < 200_000).collect::<Vec<_>>();
+
+        let handles = (1..9)
+            .map(|i| thread::spawn(move || CACHE.primes_below(i * 25_000).to_vec()))
+            .collect::<Vec<_>>();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let limit = (i as u64 + 1) * 25_000;
+            let primes = handle.join().unwrap();
+            let count = expected.iter().take_while(|&&p| p < limit).count();
+            assert_eq!(primes, &expected[..count]);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::ops::{Mul, Sub};
+use calc::roots::isqrt;
+
+/// Returns π(x), the number of primes less than or equal to x.
+/// Runs in O(x^(3/4)) time and O(sqrt(x)) space.
+pub fn prime_pi(x: u64) -> u64 {
+    lucy_hedgehog(x, |v| v - 1, |_| 1)
+}
+
+/// Returns the sum of all primes less than or equal to x. The result is a u128 because
+/// the sum overflows a u64 for x a little above 10^10.
+pub fn prime_sum(x: u64) -> u128 {
+    lucy_hedgehog(x, |v| { let v = u128::from(v); v * (v + 1) / 2 - 1 }, u128::from)
+}
+
+/// Lucy_Hedgehog's algorithm, from the Project Euler forum thread for problem 10.
+///
+/// Let S(v, p) be the sum of f(n) over 2 <= n <= v, where n is either prime or has no
+/// prime factor <= p, and f is completely multiplicative. S(v, 1) is just the sum over all
+/// 2 <= n <= v (`initial`). Sieving out prime p removes the numbers whose smallest prime
+/// factor is p, giving
+///
+///     S(v, p) = S(v, p - 1) - f(p) * (S(v / p, p - 1) - S(p - 1, p - 1))
+///
+/// and S(x, sqrt(x)) is the sum of f over the primes. Only the O(sqrt(x)) distinct values
+/// of x / i ever need to be stored. With f(n) = 1 this counts primes; with f(n) = n it
+/// sums them.
+fn lucy_hedgehog<T, F, W>(x: u64, initial: F, weight: W) -> T
+    where T: Copy + Default + PartialEq + Sub<Output = T> + Mul<Output = T>,
+          F: Fn(u64) -> T,
+          W: Fn(u64) -> T
+{
+    if x < 2 {
+        return T::default();
+    }
+
+    let r = isqrt(x) as usize;
+
+    // small[v] holds S(v) for v <= r, large[i] holds S(x / i) for i <= r.
+    // Index 0 of each is unused.
+    let mut small = vec![T::default()];
+    small.extend((1..r as u64 + 1).map(&initial));
+    let mut large = vec![T::default()];
+    large.extend((1..r as u64 + 1).map(|i| initial(x / i)));
+
+    for p in 2..r + 1 {
+        // p is prime exactly when sieving by smaller primes left it in place.
+        if small[p] == small[p - 1] {
+            continue;
+        }
+
+        let sp = small[p - 1];
+        let wp = weight(p as u64);
+        let p2 = (p * p) as u64;
+
+        let i_end = (x / p2).min(r as u64) as usize;
+        for i in 1..i_end + 1 {
+            let d = i * p;
+            let s = if d <= r { large[d] } else { small[(x / d as u64) as usize] };
+            large[i] = large[i] - wp * (s - sp);
+        }
+
+        for v in (p * p..r + 1).rev() {
+            small[v] = small[v] - wp * (small[v / p] - sp);
+        }
+    }
+
+    large[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prime::PrimeIterator;
+
+    #[test]
+    fn prime_pi_for_small_x_matches_prime_iterator() {
+        for x in 0..1000 {
+            let expected = PrimeIterator::new().take_while(|&p| p <= x).count() as u64;
+            assert_eq!(prime_pi(x), expected, "π({})", x);
+        }
+    }
+
+    #[test]
+    fn prime_pi_for_powers_of_ten_works() {
+        assert_eq!(prime_pi(10), 4);
+        assert_eq!(prime_pi(100), 25);
+        assert_eq!(prime_pi(1_000), 168);
+        assert_eq!(prime_pi(10_000), 1229);
+        assert_eq!(prime_pi(100_000), 9592);
+        assert_eq!(prime_pi(1_000_000), 78_498);
+        assert_eq!(prime_pi(1_000_000_000), 50_847_534);
+        assert_eq!(prime_pi(10_000_000_000), 455_052_511);
+    }
+
+    #[test]
+    fn prime_sum_for_small_x_matches_prime_iterator() {
+        for x in 0..1000 {
+            let expected = PrimeIterator::new().take_while(|&p| p <= x).sum::<u64>();
+            assert_eq!(prime_sum(x), u128::from(expected), "sum of primes <= {}", x);
+        }
+    }
+
+    #[test]
+    fn prime_sum_for_large_x_works() {
+        assert_eq!(prime_sum(1_999_999), 142_913_828_922);
+        assert_eq!(prime_sum(1_000_000_000), 24_739_512_092_254_535);
+        assert_eq!(prime_sum(10_000_000_000), 2_220_822_432_581_729_238);
+    }
+}
@@ -0,0 +1,172 @@
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::path::Path;
+use fnv::FnvHasher;
+
+// The on-disk format for a table of all the primes less than `limit`. All integers are
+// little-endian.
+//
+//     magic    8 bytes   b"PEPRIMES"
+//     version  u32       FORMAT_VERSION
+//     limit    u64       exclusive upper bound of the primes
+//     checksum u64       FNV-1a hash of the bitset
+//     bitset   bytes     bit i is set if 2i + 1 is prime, for all odd numbers below limit
+//
+// Storing only the odd numbers makes the file 1 bit per 2 numbers, which is much smaller
+// than the primes themselves. 2 is implied by limit > 2.
+
+const MAGIC: &[u8; 8] = b"PEPRIMES";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 8 + 4 + 8 + 8;
+
+/// Writes the primes less than limit to path. `primes` must be exactly those primes.
+/// The file is written to a temporary file first and then renamed over the old one, so a
+/// crash part way through will not leave a corrupt cache behind.
+pub fn save(path: &Path, limit: u64, primes: &[u64]) -> io::Result<()> {
+    let mut bitset = vec![0_u8; (limit / 2 + 7) as usize / 8];
+    for &p in primes.iter().filter(|&&p| p != 2) {
+        let i = (p / 2) as usize;
+        bitset[i / 8] |= 1 << (i % 8);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(&limit.to_le_bytes())?;
+        file.write_all(&checksum(&bitset).to_le_bytes())?;
+        file.write_all(&bitset)?;
+        file.flush()?;
+    }
+
+    fs::rename(&tmp_path, path)
+}
+
+/// Reads just the limit from the file header, which is much cheaper than loading the
+/// whole file when all you want to know is whether it is worth loading.
+pub fn read_limit(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    read_header(&mut file).map(|(limit, _)| limit)
+}
+
+/// Loads a table written by `save`, returning the limit and the primes less than it.
+/// Files with the wrong magic number, version or checksum are rejected with an
+/// `InvalidData` error.
+pub fn load(path: &Path) -> io::Result<(u64, Vec<u64>)> {
+    let mut file = BufReader::new(File::open(path)?);
+    let (limit, expected_checksum) = read_header(&mut file)?;
+
+    let mut bitset = Vec::new();
+    file.read_to_end(&mut bitset)?;
+    if bitset.len() != (limit / 2 + 7) as usize / 8 {
+        return Err(invalid_data("Prime cache file is truncated"));
+    }
+    if checksum(&bitset) != expected_checksum {
+        return Err(invalid_data("Prime cache file checksum does not match"));
+    }
+
+    let mut primes = Vec::new();
+    if limit > 2 {
+        primes.push(2);
+    }
+    for (byte_index, &byte) in bitset.iter().enumerate() {
+        for bit in 0..8 {
+            if byte & (1 << bit) != 0 {
+                primes.push(((byte_index * 8 + bit) * 2 + 1) as u64);
+            }
+        }
+    }
+
+    Ok((limit, primes))
+}
+
+fn read_header<R: Read>(reader: &mut R) -> io::Result<(u64, u64)> {
+    let mut header = [0_u8; HEADER_LEN];
+    reader.read_exact(&mut header)?;
+
+    if &header[0..8] != MAGIC {
+        return Err(invalid_data("Not a prime cache file"));
+    }
+
+    let mut version = [0_u8; 4];
+    version.copy_from_slice(&header[8..12]);
+    if u32::from_le_bytes(version) != FORMAT_VERSION {
+        return Err(invalid_data("Prime cache file has an unsupported version"));
+    }
+
+    let mut limit = [0_u8; 8];
+    limit.copy_from_slice(&header[12..20]);
+    let mut checksum = [0_u8; 8];
+    checksum.copy_from_slice(&header[20..28]);
+
+    Ok((u64::from_le_bytes(limit), u64::from_le_bytes(checksum)))
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Returns a directory for the files of one test. It includes the process id and the test
+/// name, so tests running in parallel, or in several test runs at once, never share files.
+#[cfg(test)]
+pub fn test_dir(test_name: &str) -> ::std::path::PathBuf {
+    ::std::env::temp_dir().join(format!("projecteuler-tests-{}-{}", ::std::process::id(), test_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prime::PrimeIterator;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = test_dir("save_and_load_round_trip");
+        let path = dir.join("primes.bin");
+        for &limit in &[0, 2, 3, 4, 17, 1000, 100_001] {
+            let primes = PrimeIterator::new().take_while(|&p| p < limit).collect::<Vec<_>>();
+            save(&path, limit, &primes).unwrap();
+            assert_eq!(read_limit(&path).unwrap(), limit);
+            assert_eq!(load(&path).unwrap(), (limit, primes));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_rejects_corrupt_files() {
+        let dir = test_dir("load_rejects_corrupt_files");
+        let path = dir.join("primes.bin");
+        let primes = PrimeIterator::new().take_while(|&p| p < 1000).collect::<Vec<_>>();
+        save(&path, 1000, &primes).unwrap();
+        let good = fs::read(&path).unwrap();
+
+        // Flip a bit in the sieve.
+        let mut bytes = good.clone();
+        bytes[HEADER_LEN + 3] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // Bump the version.
+        let mut bytes = good.clone();
+        bytes[8] += 1;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // Chop off the end.
+        fs::write(&path, &good[..good.len() - 1]).unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
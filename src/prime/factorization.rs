@@ -0,0 +1,177 @@
+use overflow;
+
+/// The prime factorisation of a number, stored as a list of `(prime, exponent)` pairs
+/// in ascending order of prime. For example 360 = 2^3 * 3^2 * 5 is stored as
+/// `[(2, 3), (3, 2), (5, 1)]`. The factorisation of 1 is empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Factorization {
+    factors: Vec<(u64, u32)>
+}
+
+impl Factorization {
+    pub fn new() -> Self {
+        Factorization { factors: Vec::new() }
+    }
+
+    /// Multiplies the factorisation by `p^exponent`. Primes must be pushed in ascending
+    /// order; pushing the same prime again just increases its exponent.
+    pub fn push(&mut self, p: u64, exponent: u32) {
+        if exponent == 0 {
+            return;
+        }
+
+        if let Some(last) = self.factors.last_mut() {
+            debug_assert!(last.0 <= p, "Primes must be pushed in ascending order");
+            if last.0 == p {
+                last.1 += exponent;
+                return;
+            }
+        }
+
+        self.factors.push((p, exponent));
+    }
+
+    /// Returns the `(prime, exponent)` pairs.
+    #[inline]
+    pub fn factors(&self) -> &[(u64, u32)] {
+        &self.factors
+    }
+
+    /// Returns true if this is the factorisation of 1.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// Returns true if the factorised number is prime.
+    pub fn is_prime(&self) -> bool {
+        self.factors.len() == 1 && self.factors[0].1 == 1
+    }
+
+    /// Multiplies the factors back together to get the original number.
+    pub fn value(&self) -> u64 {
+        self.factors.iter().fold(1, |value, &(p, e)| overflow::mul(value, overflow::pow(p, e)))
+    }
+
+    /// As `value`, but returns None if the number does not fit in a u64.
+    pub fn checked_value(&self) -> Option<u64> {
+        self.factors.iter().try_fold(1_u64, |value, &(p, e)| value.checked_mul(p.checked_pow(e)?))
+    }
+
+    /// Returns the number of divisors, τ(n), which is the product of (exponent + 1)
+    /// over all the prime factors.
+    pub fn num_divisors(&self) -> u64 {
+        self.factors.iter().map(|&(_, e)| u64::from(e) + 1).product()
+    }
+
+    /// Returns the sum of the divisors, σ(n), which is the product of
+    /// 1 + p + p^2 + ... + p^e over all the prime factors.
+    pub fn sum_of_divisors(&self) -> u64 {
+        self.factors.iter()
+            .map(|&(p, e)| (0..e).fold(1, |sum, _| overflow::add(overflow::mul(sum, p), 1)))
+            .fold(1, overflow::mul)
+    }
+
+    /// As `sum_of_divisors`, but returns None if the sum does not fit in a u64.
+    pub fn checked_sum_of_divisors(&self) -> Option<u64> {
+        self.factors.iter().try_fold(1_u64, |product, &(p, e)| {
+            let sum = (0..e).try_fold(1_u64, |sum, _| sum.checked_mul(p)?.checked_add(1))?;
+            product.checked_mul(sum)
+        })
+    }
+
+    /// Returns all the divisors, generated by multiplying together every combination of
+    /// prime powers. They are not in any particular order.
+    pub fn divisors(&self) -> Vec<u64> {
+        let mut result = vec![1];
+
+        for &(p, e) in &self.factors {
+            let len = result.len();
+            let mut power = 1;
+            for _ in 0..e {
+                power *= p;
+                for i in 0..len {
+                    let d = result[i] * power;
+                    result.push(d);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Factorization;
+
+    #[test]
+    fn push_merges_repeated_primes() {
+        let mut f = Factorization::new();
+        f.push(2, 1);
+        f.push(2, 2);
+        f.push(3, 0);
+        f.push(5, 1);
+        assert_eq!(f.factors(), &[(2, 3), (5, 1)]);
+        assert_eq!(f.value(), 40);
+    }
+
+    #[test]
+    fn empty_factorization_is_one() {
+        let f = Factorization::new();
+        assert!(f.is_empty());
+        assert!(!f.is_prime());
+        assert_eq!(f.value(), 1);
+        assert_eq!(f.num_divisors(), 1);
+    }
+
+    #[test]
+    fn num_divisors_works() {
+        let mut f = Factorization::new();
+        f.push(2, 3);
+        f.push(3, 2);
+        f.push(5, 1);
+        assert_eq!(f.value(), 360);
+        assert_eq!(f.num_divisors(), 24);
+    }
+
+    #[test]
+    fn sum_of_divisors_works() {
+        let mut f = Factorization::new();
+        f.push(2, 2);
+        f.push(7, 1);
+        assert_eq!(f.sum_of_divisors(), 56);
+        assert_eq!(Factorization::new().sum_of_divisors(), 1);
+    }
+
+    #[test]
+    fn checked_functions_work() {
+        let mut f = Factorization::new();
+        f.push(2, 2);
+        f.push(7, 1);
+        assert_eq!(f.checked_value(), Some(28));
+        assert_eq!(f.checked_sum_of_divisors(), Some(56));
+
+        // 2^63 fits, but σ(2^63) = 2^64 - 1 only just does, and 2^64 does not.
+        let mut f = Factorization::new();
+        f.push(2, 63);
+        assert_eq!(f.checked_value(), Some(1 << 63));
+        assert_eq!(f.checked_sum_of_divisors(), Some(u64::MAX));
+        f.push(2, 1);
+        assert_eq!(f.checked_value(), None);
+        assert_eq!(f.checked_sum_of_divisors(), None);
+    }
+
+    #[test]
+    fn divisors_works() {
+        let mut f = Factorization::new();
+        f.push(2, 3);
+        f.push(3, 2);
+        f.push(5, 1);
+        let mut divisors = f.divisors();
+        assert_eq!(divisors.len() as u64, f.num_divisors());
+        divisors.sort();
+        assert_eq!(divisors, (1..361).filter(|d| 360 % d == 0).collect::<Vec<_>>());
+        assert_eq!(Factorization::new().divisors(), vec![1]);
+    }
+}
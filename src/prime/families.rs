@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use prime::is_prime;
+use prime::cache::PRIMES;
+use digits::{digit_count, is_palindrome_in_base, rotate_digits_right};
+use digits::pandigital::digit_signature;
+
+// Iterators over some of the special families of primes that turn up in Project Euler.
+// The infinite families are filters over the global prime cache; the finite families are
+// built digit by digit and end once the family is exhausted.
+
+/// Twin primes (p, p + 2) where both are prime, ordered by p.
+/// The values start: (3, 5), (5, 7), (11, 13), (17, 19), (29, 31), ...
+pub fn twin_primes() -> impl Iterator<Item = (u64, u64)> {
+    PRIMES.iter()
+        .zip(PRIMES.iter().skip(1))
+        .filter(|&(p, q)| q - p == 2)
+}
+
+/// Sophie Germain primes, which are primes p where 2p + 1 is also prime.
+/// The values start: 2, 3, 5, 11, 23, 29, 41, 53, 83, 89, ...
+/// It ends once 2p + 1 no longer fits in a u64.
+pub fn sophie_germain_primes() -> impl Iterator<Item = u64> {
+    PRIMES.iter()
+        .take_while(|&p| p <= (u64::MAX - 1) / 2)
+        .filter(|&p| is_prime(2 * p + 1))
+}
+
+/// Primes which read the same backwards.
+/// The values start: 2, 3, 5, 7, 11, 101, 131, 151, 181, 191, ...
+pub fn palindromic_primes() -> impl Iterator<Item = u64> {
+    PRIMES.iter().filter(|&p| is_palindrome_in_base(p, 10))
+}
+
+/// Circular primes, where every rotation of the digits is also prime.
+/// The values start: 2, 3, 5, 7, 11, 13, 17, 31, 37, 71, ...
+/// Beware that the next one after 999331 is the repunit 1111111111111111111, so a
+/// `take_while` with a bound above 999331 will effectively never finish.
+pub fn circular_primes() -> impl Iterator<Item = u64> {
+    PRIMES.iter().filter(|&p| is_circular_prime(p))
+}
+
+/// Right-truncatable primes, which stay prime as digits are removed from the right:
+/// 3797, 379, 37, 3. There are exactly 83 of them, so this iterator is finite.
+pub fn right_truncatable_primes() -> impl Iterator<Item = u64> {
+    let mut result = Vec::new();
+    let mut level = vec![2, 3, 5, 7];
+
+    // Appending to each prime of a sorted level in turn gives the next level in sorted order.
+    while !level.is_empty() {
+        result.extend_from_slice(&level);
+        level = level.iter()
+            .flat_map(|&p| [1, 3, 7, 9].iter().map(move |&d| p * 10 + d))
+            .filter(|&n| is_prime(n))
+            .collect();
+    }
+
+    result.into_iter()
+}
+
+/// Primes which are both left- and right-truncatable, such as 3797, 797, 97, 7. There are
+/// exactly 15 of them including the single digit primes (Project Euler 37 excludes those).
+pub fn truncatable_primes() -> impl Iterator<Item = u64> {
+    right_truncatable_primes().filter(|&p| is_left_truncatable_prime(p))
+}
+
+/// Groups of primes with `num_digits` digits which are permutations of each other's digits,
+/// such as [1487, 4817, 8147]. Primes with no such partner are omitted. The groups are
+/// ordered by their smallest member, and each group is in ascending order. There are no
+/// groups with 0 digits.
+///
+/// Every prime below 10^num_digits is sieved into the global cache and grouped in memory,
+/// at 8 bytes or more per prime: about 50 MB for 8 digits, 400 MB for 9 and 4 GB for 10.
+/// Panics if 10^num_digits does not fit in a u64, which is from 20 digits on.
+pub fn permutation_prime_groups(num_digits: u32) -> impl Iterator<Item = Vec<u64>> {
+    let high = 10_u64.checked_pow(num_digits)
+        .unwrap_or_else(|| panic!("{}-digit numbers do not all fit in a u64", num_digits));
+    if num_digits == 0 {
+        return Vec::new().into_iter();
+    }
+
+    let low = high / 10;
+
+    let mut groups = BTreeMap::new();
+    for &p in PRIMES.primes_below(high).iter().filter(|&&p| p >= low) {
+        groups.entry(digit_signature(p)).or_insert_with(Vec::new).push(p);
+    }
+
+    let mut groups = groups.into_values()
+        .filter(|group| group.len() > 1)
+        .collect::<Vec<_>>();
+    groups.sort();
+    groups.into_iter()
+}
+
+fn is_circular_prime(p: u64) -> bool {
+    (1..digit_count(p, 10)).all(|k| is_prime(rotate_digits_right(p, k, 10)))
+}
+
+fn is_left_truncatable_prime(p: u64) -> bool {
+    let mut modulus = 10;
+    while modulus < p {
+        let n = p % modulus;
+        // A zero digit would be lost from the front, so the truncation would not be valid.
+        if n < modulus / 10 || !is_prime(n) {
+            return false;
+        }
+        modulus *= 10;
+    }
+
+    is_prime(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twin_primes_works() {
+        // OEIS A001359.
+        let lesser = twin_primes().map(|(p, _)| p).take(15).collect::<Vec<_>>();
+        assert_eq!(lesser, vec![3, 5, 11, 17, 29, 41, 59, 71, 101, 107, 137, 149, 179, 191, 197]);
+        assert!(twin_primes().take(100).all(|(p, q)| q == p + 2));
+    }
+
+    #[test]
+    fn sophie_germain_primes_works() {
+        // OEIS A005384.
+        assert_eq!(sophie_germain_primes().take(15).collect::<Vec<_>>(),
+                   vec![2, 3, 5, 11, 23, 29, 41, 53, 83, 89, 113, 131, 173, 179, 191]);
+    }
+
+    #[test]
+    fn palindromic_primes_works() {
+        // OEIS A002385.
+        assert_eq!(palindromic_primes().take(15).collect::<Vec<_>>(),
+                   vec![2, 3, 5, 7, 11, 101, 131, 151, 181, 191, 313, 353, 373, 383, 727]);
+    }
+
+    #[test]
+    fn circular_primes_works() {
+        // OEIS A068652.
+        assert_eq!(circular_primes().take(15).collect::<Vec<_>>(),
+                   vec![2, 3, 5, 7, 11, 13, 17, 31, 37, 71, 73, 79, 97, 113, 131]);
+        // Project Euler 35.
+        assert_eq!(PRIMES.primes_below(1_000_000).iter().filter(|&&p| is_circular_prime(p)).count(), 55);
+    }
+
+    #[test]
+    fn right_truncatable_primes_works() {
+        // OEIS A024770.
+        assert_eq!(right_truncatable_primes().take(15).collect::<Vec<_>>(),
+                   vec![2, 3, 5, 7, 23, 29, 31, 37, 53, 59, 71, 73, 79, 233, 239]);
+        assert_eq!(right_truncatable_primes().count(), 83);
+        assert_eq!(right_truncatable_primes().last(), Some(73_939_133));
+    }
+
+    #[test]
+    fn truncatable_primes_works() {
+        // OEIS A020994.
+        assert_eq!(truncatable_primes().collect::<Vec<_>>(),
+                   vec![2, 3, 5, 7, 23, 37, 53, 73, 313, 317, 373, 797, 3137, 3797, 739_397]);
+        // Project Euler 37.
+        assert_eq!(truncatable_primes().filter(|&p| p > 7).sum::<u64>(), 748_317);
+    }
+
+    #[test]
+    fn permutation_prime_groups_works() {
+        assert_eq!(permutation_prime_groups(2).collect::<Vec<_>>(),
+                   vec![vec![13, 31], vec![17, 71], vec![37, 73], vec![79, 97]]);
+        assert_eq!(permutation_prime_groups(0).count(), 0);
+        assert_eq!(permutation_prime_groups(1).count(), 0);
+
+        // Project Euler 49.
+        let groups = permutation_prime_groups(4).collect::<Vec<_>>();
+        assert!(groups.iter().any(|g| g.contains(&1487) && g.contains(&4817) && g.contains(&8147)));
+        assert!(groups.iter().any(|g| g.contains(&2969) && g.contains(&6299) && g.contains(&9629)));
+        assert!(groups.iter().all(|g| g.len() > 1 && g.windows(2).all(|w| w[0] < w[1])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn permutation_prime_groups_panics_beyond_u64() {
+        permutation_prime_groups(20).count();
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::{Add, Sub, Mul, Div, Rem};
+
+/// The unsigned integer types which the prime functions work with: u32, u64 and u128.
+pub trait PrimeInt: Copy + Ord + Hash + Debug + Display
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+    + Div<Output = Self> + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Converts from a u64, returning None if the value does not fit.
+    fn from_u64(n: u64) -> Option<Self>;
+
+    /// Widens to a u128, which every implementation fits in.
+    fn to_u128(self) -> u128;
+}
+
+macro_rules! impl_prime_int {
+    ($t:ty) => {
+        impl PrimeInt for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            #[inline]
+            fn from_u64(n: u64) -> Option<Self> {
+                if u128::from(n) <= <$t>::MAX as u128 {
+                    Some(n as $t)
+                } else {
+                    None
+                }
+            }
+
+            #[inline]
+            fn to_u128(self) -> u128 {
+                self as u128
+            }
+        }
+    }
+}
+
+impl_prime_int!(u32);
+impl_prime_int!(u64);
+impl_prime_int!(u128);
+
+#[cfg(test)]
+mod tests {
+    use super::PrimeInt;
+
+    #[test]
+    fn from_u64_works() {
+        assert_eq!(u32::from_u64(7), Some(7_u32));
+        assert_eq!(u32::from_u64(1 << 32), None);
+        assert_eq!(u64::from_u64(u64::MAX), Some(u64::MAX));
+        assert_eq!(u128::from_u64(u64::MAX), Some(u128::from(u64::MAX)));
+    }
+}
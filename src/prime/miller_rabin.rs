@@ -0,0 +1,285 @@
+// Miller-Rabin primality testing for u64 and u128.
+//
+// For n < 2^64 the test is deterministic: the bases below are known to have no strong
+// pseudoprimes in common in that range. For larger n the first 20 primes are used as
+// bases, which is proven correct below 3.3 * 10^24. The bases are fixed, so beyond that
+// the test is still deterministic but unproven: composites which are strong pseudoprimes
+// to all 20 bases exist and can be constructed, and they are reported as prime.
+
+const SMALL_PRIMES: [u64; 20] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71];
+
+const U64_BASES: [u64; 7] = [2, 325, 9375, 28178, 450_775, 9_780_504, 1_795_265_022];
+
+pub fn is_prime_u64(n: u64) -> bool {
+    if let Some(result) = small_prime_check(u128::from(n)) {
+        return result;
+    }
+
+    let (d, s) = split_power_of_two(n - 1);
+    let mul = |a: u64, b: u64| ((u128::from(a) * u128::from(b)) % u128::from(n)) as u64;
+
+    'bases: for &base in &U64_BASES {
+        let a = base % n;
+        if a == 0 {
+            continue;
+        }
+
+        let mut x = pow(a, d, 1, &mul);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul(x, x);
+            if x == n - 1 {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+pub fn is_prime_u128(n: u128) -> bool {
+    if n <= u128::from(u64::MAX) {
+        return is_prime_u64(n as u64);
+    }
+    if let Some(result) = small_prime_check(n) {
+        return result;
+    }
+
+    let m = Montgomery::new(n);
+    let one = m.one();
+    let minus_one = n - one;
+    let (d, s) = split_power_of_two(n - 1);
+    let mul = |a: u128, b: u128| m.mul(a, b);
+
+    'bases: for &base in &SMALL_PRIMES {
+        let mut x = pow(m.to_montgomery(u128::from(base)), d, one, &mul);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = m.mul(x, x);
+            if x == minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+/// Handles n < 2 and n with a small prime factor, returning None if n needs the full test.
+fn small_prime_check(n: u128) -> Option<bool> {
+    if n < 2 {
+        return Some(false);
+    }
+
+    for &p in &SMALL_PRIMES {
+        let p = u128::from(p);
+        if n == p {
+            return Some(true);
+        }
+        if n.is_multiple_of(p) {
+            return Some(false);
+        }
+    }
+
+    // No factor up to 71, so anything below 73^2 is prime.
+    if n < 73 * 73 {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Writes n as d * 2^s with d odd.
+fn split_power_of_two<T>(n: T) -> (T, u32)
+    where T: Copy + PartialEq + ::std::ops::Shr<u32, Output = T> + ::std::ops::BitAnd<Output = T> + From<u8>
+{
+    let mut d = n;
+    let mut s = 0;
+    while d & T::from(1) == T::from(0) {
+        d = d >> 1;
+        s += 1;
+    }
+    (d, s)
+}
+
+/// Computes base^exp by repeated squaring, where `one` is the multiplicative identity
+/// under `mul`.
+fn pow<T, F>(mut base: T, mut exp: T, one: T, mul: &F) -> T
+    where T: Copy + PartialEq + ::std::ops::Shr<u32, Output = T> + ::std::ops::BitAnd<Output = T> + From<u8>,
+          F: Fn(T, T) -> T
+{
+    let mut result = one;
+    while exp != T::from(0) {
+        if exp & T::from(1) == T::from(1) {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp = exp >> 1;
+    }
+    result
+}
+
+/// Montgomery multiplication modulo an odd u128, with R = 2^128. Numbers are held in
+/// Montgomery form aR mod n, which lets products be reduced with multiplications and
+/// shifts instead of a (very slow) 256-bit division.
+struct Montgomery {
+    n: u128,
+    // -n^-1 mod 2^128.
+    n_neg_inv: u128,
+    // R^2 mod n, used to convert into Montgomery form.
+    r2: u128
+}
+
+impl Montgomery {
+    fn new(n: u128) -> Self {
+        debug_assert!(n % 2 == 1);
+
+        // Newton's iteration doubles the number of correct low bits each time, and n is its
+        // own inverse mod 8, so 7 iterations give 3 * 2^7 >= 128 bits.
+        let mut inv = n;
+        for _ in 0..7 {
+            inv = inv.wrapping_mul(2_u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+
+        // R mod n, then double it 128 more times to get R^2 mod n.
+        let mut r2 = (u128::MAX % n + 1) % n;
+        for _ in 0..128 {
+            r2 = add_mod(r2, r2, n);
+        }
+
+        Montgomery { n, n_neg_inv: inv.wrapping_neg(), r2 }
+    }
+
+    /// Returns 1 in Montgomery form, which is R mod n.
+    fn one(&self) -> u128 {
+        self.reduce(0, self.r2)
+    }
+
+    fn to_montgomery(&self, a: u128) -> u128 {
+        self.mul(a % self.n, self.r2)
+    }
+
+    /// Returns abR^-1 mod n.
+    fn mul(&self, a: u128, b: u128) -> u128 {
+        let (hi, lo) = mul_wide(a, b);
+        self.reduce(hi, lo)
+    }
+
+    /// Returns (hi * 2^128 + lo) * R^-1 mod n, for inputs less than nR.
+    fn reduce(&self, hi: u128, lo: u128) -> u128 {
+        let m = lo.wrapping_mul(self.n_neg_inv);
+        let (mn_hi, mn_lo) = mul_wide(m, self.n);
+        // lo + mn_lo is a multiple of R, so it only contributes a carry.
+        let carry = (lo.overflowing_add(mn_lo).1) as u128;
+        let (t, overflow) = hi.overflowing_add(mn_hi);
+        let (t, overflow2) = t.overflowing_add(carry);
+        if overflow || overflow2 || t >= self.n {
+            t.wrapping_sub(self.n)
+        } else {
+            t
+        }
+    }
+}
+
+fn add_mod(a: u128, b: u128, n: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= n {
+        sum.wrapping_sub(n)
+    } else {
+        sum
+    }
+}
+
+/// Returns the full 256-bit product of a and b as (high, low) halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = (1 << 64) - 1;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let middle = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (middle << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+    (hi, lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prime::PrimeIterator;
+
+    #[test]
+    fn is_prime_u64_matches_prime_iterator() {
+        let primes = PrimeIterator::new().take_while(|&p| p < 100_000).collect::<Vec<_>>();
+        for n in 0..100_000 {
+            assert_eq!(is_prime_u64(n), primes.binary_search(&n).is_ok(), "is_prime_u64({})", n);
+        }
+    }
+
+    #[test]
+    fn is_prime_u64_for_large_numbers_works() {
+        assert!(is_prime_u64(18_446_744_073_709_551_557)); // Largest u64 prime.
+        assert!(!is_prime_u64(u64::MAX));
+        assert!(is_prime_u64(4_294_967_291)); // Largest u32 prime.
+        assert!(!is_prime_u64(4_294_967_291 * 4_294_967_279));
+        // Strong pseudoprimes to several small bases.
+        assert!(!is_prime_u64(3_215_031_751));
+        assert!(!is_prime_u64(3_825_123_056_546_413_051));
+    }
+
+    #[test]
+    fn is_prime_u128_works() {
+        assert!(is_prime_u128(u128::MAX - 158)); // Largest u128 prime, 2^128 - 159.
+        assert!(!is_prime_u128(u128::MAX));
+        assert!(is_prime_u128((1 << 89) - 1)); // Mersenne prime.
+        assert!(!is_prime_u128((1 << 67) - 1)); // 2^67 - 1 = 193707721 * 761838257287.
+        assert!(is_prime_u128(18_446_744_073_709_551_629)); // Smallest prime above 2^64.
+        // The product of the two largest u64 primes.
+        assert!(!is_prime_u128(18_446_744_073_709_551_557 * 18_446_744_073_709_551_533));
+        // A strong pseudoprime to the first 13 prime bases.
+        assert!(!is_prime_u128(3_317_044_064_679_887_385_961_981));
+    }
+
+    #[test]
+    fn mul_wide_works() {
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(mul_wide(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(mul_wide(12345, 67890), (0, 12345 * 67890));
+    }
+
+    #[test]
+    fn montgomery_mul_works() {
+        let n = u128::MAX - 158;
+        let m = Montgomery::new(n);
+        let a = 123_456_789_012_345_678_901_234_567;
+        let b = 987_654_321_098_765_432_109_876_543;
+        let product = m.mul(m.to_montgomery(a), m.to_montgomery(b));
+        // Converting back out of Montgomery form is a multiplication by 1.
+        let expected = mul_mod_slow(a, b, n);
+        assert_eq!(m.mul(product, 1), expected);
+    }
+
+    /// Double-and-add multiplication, which is slow but obviously correct.
+    fn mul_mod_slow(a: u128, mut b: u128, n: u128) -> u128 {
+        let mut result = 0;
+        let mut a = a % n;
+        while b > 0 {
+            if b & 1 == 1 {
+                result = add_mod(result, a, n);
+            }
+            a = add_mod(a, a, n);
+            b >>= 1;
+        }
+        result
+    }
+}
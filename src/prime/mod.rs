@@ -0,0 +1,309 @@
+use std::convert::TryFrom;
+use std::ops::Range;
+
+pub mod analysis;
+pub mod arithmetic;
+pub mod cache;
+pub mod counting;
+pub mod disk;
+pub mod families;
+pub mod sieve;
+mod factorization;
+mod int;
+mod miller_rabin;
+mod spf;
+pub mod wheel;
+
+pub use self::factorization::Factorization;
+pub use self::int::PrimeInt;
+pub use self::spf::SpfSieve;
+use self::cache::{Primes, PrimeCache, PRIMES};
+use self::wheel::{Wheel, WheelCandidates, WHEEL_30};
+
+// TODO: Create an "in range" iterator adaptor.
+pub fn primes_in_range(r: Range<u64>) -> Vec<u64> {
+    PrimeIterator::new()
+        .skip_while(|&p| p < r.start)
+        .take_while(|&p| p < r.end)
+        .collect()
+}
+
+/// An iterator that yields prime numbers of type `T`, which can be u32, u64 or u128.
+/// The values start: 2, 3, 5, 7, 11, 13, 17, 19, ...
+/// If the global prime cache has a disk cache enabled, the primes stored there are yielded
+/// first, and trial division only starts after them. The primes found after that are added
+/// back to the cache as the iterator goes, each time it has doubled what the cache had, and
+/// when it is dropped.
+/// A `PrimeIterator<u32>` ends after the largest u32 prime. Candidates are generated as u64,
+/// so a `PrimeIterator<u128>` never gets past 2^64, but trial division would take forever
+/// to get that far anyway.
+pub struct PrimeIterator<T: PrimeInt = u64> {
+    primes: Vec<T>,
+    // Primes from the cache which are yielded before any candidates are tried.
+    stored: Option<Primes>,
+    // The cache to add new primes to, and how many of the primes found so far it has.
+    cache: Option<&'static PrimeCache>,
+    cached: usize,
+    candidates: WheelCandidates,
+    // Candidates are never divisible by the wheel primes, so trial division can skip them.
+    skip: usize
+}
+
+impl PrimeIterator {
+    pub fn new() -> Self {
+        PrimeIterator::with_wheel(&WHEEL_30)
+    }
+}
+
+impl<T: PrimeInt> PrimeIterator<T> {
+    /// Creates a prime iterator which generates candidates using the specified wheel.
+    pub fn with_wheel(wheel: &'static Wheel) -> Self {
+        PrimeIterator::with_cache(wheel, &PRIMES)
+    }
+
+    /// Creates a prime iterator which starts with the primes stored in cache, if it has a
+    /// disk cache enabled, and then generates candidates using the specified wheel.
+    pub fn with_cache(wheel: &'static Wheel, cache: &'static PrimeCache) -> Self {
+        let stored = cache.stored_primes();
+        let candidates = match stored.as_ref().and_then(|primes| primes.last()) {
+            Some(&p) => wheel.candidates_from(p + 1),
+            None => wheel.candidates()
+        };
+        let cached = stored.as_ref().map_or(0, |primes| primes.len());
+        let cache = stored.as_ref().map(|_| cache);
+        PrimeIterator { primes: Vec::new(), stored, cache, cached, candidates, skip: wheel.primes().len() }
+    }
+
+    /// Adds the primes found so far to the cache, if there is one and it does not have them.
+    fn save_to_cache(&mut self) {
+        let cache = match self.cache {
+            Some(cache) if self.primes.len() > self.cached => cache,
+            _ => return
+        };
+
+        // Candidates are u64s, so every prime found fits in one.
+        let primes = self.primes.iter()
+            .map(|&p| u64::try_from(p.to_u128()).expect("Primes are generated as u64"))
+            .collect::<Vec<_>>();
+        let limit = primes[primes.len() - 1] + 1;
+        cache.extend_with(primes, limit);
+        self.cached = self.primes.len();
+    }
+}
+
+impl<T: PrimeInt> Default for PrimeIterator<T> {
+    fn default() -> Self {
+        PrimeIterator::with_wheel(&WHEEL_30)
+    }
+}
+
+impl<T: PrimeInt> Iterator for PrimeIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if let Some(ref stored) = self.stored {
+            if self.primes.len() < stored.len() {
+                let p = T::from_u64(stored[self.primes.len()])?;
+                self.primes.push(p);
+                return Some(p);
+            }
+        }
+
+        loop {
+            let n = T::from_u64(self.candidates.next()?)?;
+            let skip = self.skip.min(self.primes.len());
+            if is_prime_by_known(&self.primes[skip..], n) {
+                self.primes.push(n);
+                if self.primes.len() >= 2 * self.cached.max(1000) {
+                    self.save_to_cache();
+                }
+                return Some(n);
+            }
+        }
+    }
+}
+
+impl<T: PrimeInt> Drop for PrimeIterator<T> {
+    fn drop(&mut self) {
+        self.save_to_cache();
+    }
+}
+
+/// Checks whether n is prime using the Miller-Rabin test. This is exact for all
+/// n < 3.3 * 10^24, which covers every u32 and u64. Above that, which only u128 can reach,
+/// it may report a composite as prime, though never a prime as composite.
+pub fn is_prime<T: PrimeInt>(n: T) -> bool {
+    miller_rabin::is_prime_u128(n.to_u128())
+}
+
+/// Factorises n by trial division by the candidates of a mod 30 wheel.
+/// The factorisation of 0 and 1 is empty.
+pub fn factorize(n: u64) -> Factorization {
+    factorize_with(n, WHEEL_30.candidates())
+}
+
+/// Factorises n by trial division by the primes from `primes`, which must yield the primes
+/// in order starting from 2, such as `PRIMES.iter()` or `PrimeIterator::new()`. It may
+/// also yield some composites, like wheel candidates do, since by the time a composite is
+/// reached its prime factors have already been divided out. Primes are only taken from it
+/// until the remaining cofactor is known to be prime. If it runs out before sqrt(n), trial
+/// division carries on from the last value it yielded, so a short slice of primes still
+/// gives the right answer, just more slowly.
+/// The factorisation of 0 and 1 is empty.
+pub fn factorize_with<I>(mut n: u64, primes: I) -> Factorization
+    where I: IntoIterator<Item = u64>
+{
+    let mut result = Factorization::new();
+    if n < 2 {
+        return result;
+    }
+
+    let mut next = 2;
+    for p in primes {
+        if p > n / p {
+            return finish_factorization(n, result);
+        }
+        divide_out(&mut n, p, &mut result);
+        next = p + 1;
+    }
+
+    for p in next.. {
+        if p > n / p {
+            break;
+        }
+        divide_out(&mut n, p, &mut result);
+    }
+
+    finish_factorization(n, result)
+}
+
+/// Divides every factor of p out of n, recording them in result.
+fn divide_out(n: &mut u64, p: u64, result: &mut Factorization) {
+    let mut exponent = 0;
+    while n.is_multiple_of(p) {
+        *n /= p;
+        exponent += 1;
+    }
+    result.push(p, exponent);
+}
+
+/// Adds the cofactor left after trial division, which is 1 or a prime.
+fn finish_factorization(n: u64, mut result: Factorization) -> Factorization {
+    if n > 1 {
+        result.push(n, 1);
+    }
+    result
+}
+
+/// Checks whether n is prime, given all the primes less than sqrt(n) which it might be
+/// divisible by.
+fn is_prime_by_known<T: PrimeInt>(known_primes: &[T], n: T) -> bool {
+    for &p in known_primes {
+        if p > n / p {
+            return true;
+        }
+        if n % p == T::ZERO {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primes_in_range_for_empty_range_returns_empty_vec() {
+        assert_eq!(primes_in_range(5..5), Vec::new());
+    }
+
+    #[test]
+    fn primes_in_range_for_start_at_zero_returns_first_primes() {
+        assert_eq!(primes_in_range(0..1),  vec![]);
+        assert_eq!(primes_in_range(0..2),  vec![]);
+        assert_eq!(primes_in_range(0..3),  vec![2]);
+        assert_eq!(primes_in_range(0..4),  vec![2, 3]);
+        assert_eq!(primes_in_range(0..5),  vec![2, 3]);
+        assert_eq!(primes_in_range(0..6),  vec![2, 3, 5]);
+        assert_eq!(primes_in_range(0..7),  vec![2, 3, 5]);
+        assert_eq!(primes_in_range(0..8),  vec![2, 3, 5, 7]);
+        assert_eq!(primes_in_range(0..9),  vec![2, 3, 5, 7]);
+        assert_eq!(primes_in_range(0..10), vec![2, 3, 5, 7]);
+        assert_eq!(primes_in_range(0..11), vec![2, 3, 5, 7]);
+        assert_eq!(primes_in_range(0..12), vec![2, 3, 5, 7, 11]);
+        assert_eq!(primes_in_range(0..13), vec![2, 3, 5, 7, 11]);
+        assert_eq!(primes_in_range(0..14), vec![2, 3, 5, 7, 11, 13]);
+    }
+
+    #[test]
+    fn primes_in_range_for_start_at_nonzero_returns_correct_primes() {
+        assert_eq!(primes_in_range(10..20), vec![11, 13, 17, 19]);
+    }
+
+    #[test]
+    fn prime_iterator_is_the_same_for_every_wheel() {
+        let expected = PrimeIterator::<u64>::with_wheel(&wheel::WHEEL_2).take(2000).collect::<Vec<_>>();
+        assert_eq!(PrimeIterator::<u64>::with_wheel(&wheel::WHEEL_30).take(2000).collect::<Vec<_>>(), expected);
+        assert_eq!(PrimeIterator::<u64>::with_wheel(&wheel::WHEEL_210).take(2000).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn is_prime_works() {
+        let primes = primes_in_range(0..10_000);
+        for n in 0..10_000 {
+            assert_eq!(is_prime(n), primes.contains(&n), "is_prime({})", n);
+        }
+    }
+
+    #[test]
+    fn factorize_works() {
+        assert!(factorize(0).is_empty());
+        assert!(factorize(1).is_empty());
+        assert_eq!(factorize(2).factors(), &[(2, 1)]);
+        assert_eq!(factorize(49).factors(), &[(7, 2)]);
+        assert_eq!(factorize(360).factors(), &[(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(600_851_475_143).factors(), &[(71, 1), (839, 1), (1471, 1), (6857, 1)]);
+
+        let sieve = SpfSieve::new(10_000);
+        for n in 1..10_001 {
+            assert_eq!(factorize(n), sieve.factorize(n));
+        }
+    }
+
+    #[test]
+    fn factorize_with_works() {
+        assert!(factorize_with(1, PrimeIterator::new()).is_empty());
+        assert_eq!(factorize_with(360, cache::PRIMES.iter()).factors(), &[(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize_with(600_851_475_143, PrimeIterator::new()).factors(), &[(71, 1), (839, 1), (1471, 1), (6857, 1)]);
+
+        // A slice of primes works too, even one that stops short of sqrt(n).
+        let primes = primes_in_range(0..100);
+        for n in 1..10_001 {
+            assert_eq!(factorize_with(n, primes.iter().cloned()), factorize(n));
+        }
+        for n in (1..20_001).chain(1_000_000_000..1_000_001_000) {
+            assert_eq!(factorize_with(n, primes[..5].iter().cloned()), factorize(n));
+            assert_eq!(factorize_with(n, None), factorize(n));
+        }
+        assert_eq!(factorize_with(97 * 97 * 101, vec![2, 3]).factors(), &[(97, 2), (101, 1)]);
+    }
+
+    #[test]
+    fn prime_iterator_is_the_same_for_every_type() {
+        let expected = PrimeIterator::new().take(2000).collect::<Vec<_>>();
+        let primes32 = PrimeIterator::<u32>::default().take(2000).map(u64::from).collect::<Vec<_>>();
+        let primes128 = PrimeIterator::<u128>::default().take(2000).collect::<Vec<_>>();
+        assert_eq!(primes32, expected);
+        assert_eq!(primes128, expected.iter().map(|&p| u128::from(p)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn is_prime_is_generic() {
+        assert!(is_prime(4_294_967_291_u32));
+        assert!(!is_prime(4_294_967_295_u32));
+        assert!(is_prime(1_000_000_007_u64));
+        assert!(is_prime(170_141_183_460_469_231_731_687_303_715_884_105_727_u128)); // 2^127 - 1
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use calc;
+use prime::cache::{PRIMES, Primes};
+
+/// A segmented sieve of Eratosthenes for all the primes less than `limit`. The range is
+/// split into segments small enough to stay in cache, and the segments are shared out
+/// between a configurable number of threads. Only the primes up to sqrt(limit) are ever
+/// stored, so large bounds such as 10^10 need very little memory.
+pub struct SegmentedSieve {
+    limit: u64,
+    segment_size: u64,
+    threads: usize,
+    base_primes: Primes
+}
+
+impl SegmentedSieve {
+    /// Creates a sieve which will use one thread per CPU.
+    pub fn new(limit: u64) -> Self {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        SegmentedSieve {
+            limit,
+            segment_size: 1 << 18,
+            threads,
+            base_primes: PRIMES.primes_below(calc::sqrt_upper_bound(limit))
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "At least one thread is required");
+        self.threads = threads;
+        self
+    }
+
+    pub fn with_segment_size(mut self, segment_size: u64) -> Self {
+        assert!(segment_size > 0, "Segments cannot be empty");
+        self.segment_size = segment_size;
+        self
+    }
+
+    /// Returns the number of primes less than the limit.
+    pub fn count(&self) -> u64 {
+        self.fold_segments(|s| s.primes().count() as u64).into_iter().sum()
+    }
+
+    /// Returns the sum of the primes less than the limit.
+    pub fn sum(&self) -> u128 {
+        self.fold_segments(|s| s.primes().map(u128::from).sum::<u128>()).into_iter().sum()
+    }
+
+    /// Applies f to every segment, in parallel, and returns the results in segment order.
+    pub fn fold_segments<T, F>(&self, f: F) -> Vec<T>
+        where F: Fn(&Segment) -> T + Sync,
+              T: Send
+    {
+        let num_segments = self.limit.div_ceil(self.segment_size) as usize;
+        let next_segment = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(num_segments));
+
+        let work = || {
+            loop {
+                let i = next_segment.fetch_add(1, Ordering::Relaxed);
+                if i >= num_segments {
+                    break;
+                }
+
+                let low = i as u64 * self.segment_size;
+                let high = self.limit.min(low + self.segment_size);
+                let result = f(&Segment::new(low, high, &self.base_primes));
+                results.lock().unwrap().push((i, result));
+            }
+        };
+
+        if self.threads == 1 {
+            work();
+        } else {
+            thread::scope(|scope| {
+                for _ in 0..self.threads {
+                    scope.spawn(work);
+                }
+            });
+        }
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|&(i, _)| i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+/// One sieved segment, covering the numbers in low..high.
+pub struct Segment {
+    low: u64,
+    high: u64,
+    is_composite: Vec<bool>
+}
+
+impl Segment {
+    /// Sieves low..high. `base_primes` must contain every prime less than sqrt(high).
+    pub fn new(low: u64, high: u64, base_primes: &[u64]) -> Self {
+        let mut is_composite = vec![false; (high - low) as usize];
+
+        // 0 and 1 are not prime, but nothing below will cross them off.
+        for n in low..high.min(2) {
+            is_composite[(n - low) as usize] = true;
+        }
+
+        for &p in base_primes {
+            if p * p >= high {
+                break;
+            }
+
+            let mut m = (p * p).max(low.div_ceil(p) * p);
+            while m < high {
+                is_composite[(m - low) as usize] = true;
+                m += p;
+            }
+        }
+
+        Segment { low, high, is_composite }
+    }
+
+    #[inline]
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+
+    #[inline]
+    pub fn high(&self) -> u64 {
+        self.high
+    }
+
+    /// Returns the primes in the segment, in ascending order.
+    pub fn primes<'a>(&'a self) -> impl Iterator<Item = u64> + 'a {
+        let low = self.low;
+        self.is_composite.iter()
+            .enumerate()
+            .filter(|&(_, &composite)| !composite)
+            .map(move |(i, _)| low + i as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prime::PrimeIterator;
+
+    #[test]
+    fn segment_works() {
+        let base_primes = [2, 3, 5, 7];
+        assert_eq!(Segment::new(0, 20, &base_primes).primes().collect::<Vec<_>>(), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(Segment::new(1, 3, &base_primes).primes().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(Segment::new(90, 110, &base_primes).primes().collect::<Vec<_>>(), vec![97, 101, 103, 107, 109]);
+        assert_eq!(Segment::new(5, 5, &base_primes).primes().count(), 0);
+    }
+
+    #[test]
+    fn count_and_sum_match_prime_iterator() {
+        let primes = PrimeIterator::new().take_while(|&p| p < 1_000_000).collect::<Vec<_>>();
+        let sum = primes.iter().map(|&p| u128::from(p)).sum::<u128>();
+
+        for &threads in &[1, 3, 8] {
+            for &segment_size in &[1000, 4099, 1 << 18] {
+                let sieve = SegmentedSieve::new(1_000_000).with_threads(threads).with_segment_size(segment_size);
+                assert_eq!(sieve.count(), primes.len() as u64);
+                assert_eq!(sieve.sum(), sum);
+            }
+        }
+    }
+
+    #[test]
+    fn small_limits_work() {
+        for limit in 0..100 {
+            let sieve = SegmentedSieve::new(limit).with_threads(2).with_segment_size(7);
+            assert_eq!(sieve.count(), PrimeIterator::new().take_while(|&p| p < limit).count() as u64);
+        }
+    }
+
+    #[test]
+    fn fold_segments_returns_results_in_order() {
+        let sieve = SegmentedSieve::new(1000).with_threads(4).with_segment_size(100);
+        let primes = sieve.fold_segments(|s| s.primes().collect::<Vec<_>>())
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(primes, PrimeIterator::new().take_while(|&p| p < 1000).collect::<Vec<_>>());
+
+        let bounds = sieve.fold_segments(|s| (s.low(), s.high()));
+        assert_eq!(bounds.first(), Some(&(0, 100)));
+        assert_eq!(bounds.last(), Some(&(900, 1000)));
+    }
+}
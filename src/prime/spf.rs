@@ -0,0 +1,126 @@
+use prime::Factorization;
+
+/// A sieve which records the smallest prime factor of every number up to and including
+/// `limit`. It is built with a linear sieve, so each composite is written exactly once.
+/// Once built, any n in range can be factorised in O(log n) by repeatedly dividing by its
+/// smallest prime factor, which makes it ideal for factorising lots of small numbers.
+pub struct SpfSieve {
+    spf: Vec<u32>,
+    primes: Vec<u32>
+}
+
+impl SpfSieve {
+    pub fn new(limit: u64) -> Self {
+        assert!(limit <= u64::from(u32::MAX), "SpfSieve limit must fit in a u32");
+
+        let len = limit as usize + 1;
+        let mut spf = vec![0_u32; len];
+        let mut primes = Vec::new();
+
+        for i in 2..len {
+            if spf[i] == 0 {
+                spf[i] = i as u32;
+                primes.push(i as u32);
+            }
+
+            // Every composite m = p * i is visited exactly once, with p = spf(m).
+            let spf_i = spf[i];
+            for &p in &primes {
+                if p > spf_i {
+                    break;
+                }
+                let m = p as usize * i;
+                if m >= len {
+                    break;
+                }
+                spf[m] = p;
+            }
+        }
+
+        SpfSieve { spf, primes }
+    }
+
+    /// Returns the largest number that this sieve can factorise.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        (self.spf.len() - 1) as u64
+    }
+
+    /// Returns the primes up to and including the limit, in ascending order.
+    #[inline]
+    pub fn primes(&self) -> &[u32] {
+        &self.primes
+    }
+
+    /// Returns the smallest prime factor of n, or None for 0 and 1.
+    #[inline]
+    pub fn smallest_prime_factor(&self, n: u64) -> Option<u64> {
+        match self.spf[n as usize] {
+            0 => None,
+            p => Some(u64::from(p))
+        }
+    }
+
+    #[inline]
+    pub fn is_prime(&self, n: u64) -> bool {
+        n >= 2 && u64::from(self.spf[n as usize]) == n
+    }
+
+    /// Factorises n, which must be in the range 1..=limit.
+    pub fn factorize(&self, mut n: u64) -> Factorization {
+        assert!(n >= 1 && n <= self.limit(), "{} is outside the range of the sieve", n);
+
+        let mut result = Factorization::new();
+        while n > 1 {
+            let p = u64::from(self.spf[n as usize]);
+            let mut exponent = 0;
+            while n.is_multiple_of(p) {
+                n /= p;
+                exponent += 1;
+            }
+            result.push(p, exponent);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpfSieve;
+
+    #[test]
+    fn smallest_prime_factor_works() {
+        let sieve = SpfSieve::new(30);
+        assert_eq!(sieve.smallest_prime_factor(0), None);
+        assert_eq!(sieve.smallest_prime_factor(1), None);
+        assert_eq!(sieve.smallest_prime_factor(2), Some(2));
+        assert_eq!(sieve.smallest_prime_factor(9), Some(3));
+        assert_eq!(sieve.smallest_prime_factor(25), Some(5));
+        assert_eq!(sieve.smallest_prime_factor(29), Some(29));
+        assert_eq!(sieve.smallest_prime_factor(30), Some(2));
+    }
+
+    #[test]
+    fn primes_works() {
+        let sieve = SpfSieve::new(30);
+        assert_eq!(sieve.primes(), &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(sieve.is_prime(29));
+        assert!(!sieve.is_prime(1));
+        assert!(!sieve.is_prime(27));
+    }
+
+    #[test]
+    fn factorize_works() {
+        let sieve = SpfSieve::new(1000);
+        assert!(sieve.factorize(1).is_empty());
+        assert_eq!(sieve.factorize(2).factors(), &[(2, 1)]);
+        assert_eq!(sieve.factorize(360).factors(), &[(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(sieve.factorize(997).factors(), &[(997, 1)]);
+        assert_eq!(sieve.factorize(1000).factors(), &[(2, 3), (5, 3)]);
+
+        for n in 1..1001 {
+            assert_eq!(sieve.factorize(n).value(), n);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+/// A factorisation wheel. Every prime is either one of the wheel's `primes` or is
+/// congruent, modulo the product of those primes, to one of the `residues`. Stepping
+/// through the residues therefore skips all multiples of the wheel primes: the mod 30
+/// wheel only visits 8 numbers in every 30, compared to 15 when stepping by 2.
+pub struct Wheel {
+    modulus: u64,
+    primes: &'static [u64],
+    residues: &'static [u64]
+}
+
+/// The trivial wheel, which just skips even numbers.
+pub static WHEEL_2: Wheel = Wheel {
+    modulus: 2,
+    primes: &[2],
+    residues: &[1]
+};
+
+pub static WHEEL_30: Wheel = Wheel {
+    modulus: 30,
+    primes: &[2, 3, 5],
+    residues: &[1, 7, 11, 13, 17, 19, 23, 29]
+};
+
+pub static WHEEL_210: Wheel = Wheel {
+    modulus: 210,
+    primes: &[2, 3, 5, 7],
+    residues: &[
+        1, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67,
+        71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 121, 127, 131, 137, 139,
+        143, 149, 151, 157, 163, 167, 169, 173, 179, 181, 187, 191, 193, 197, 199, 209
+    ]
+};
+
+impl Wheel {
+    /// Returns the primes which the wheel skips multiples of.
+    #[inline]
+    pub fn primes(&self) -> &'static [u64] {
+        self.primes
+    }
+
+    /// Returns an iterator over the prime candidates: the wheel primes followed by
+    /// every number greater than 1 which is coprime to all of them.
+    pub fn candidates(&'static self) -> WheelCandidates {
+        self.candidates_from(0)
+    }
+
+    /// Returns an iterator over the prime candidates which are >= start. It ends at the
+    /// last candidate which fits in a u64.
+    pub fn candidates_from(&'static self, start: u64) -> WheelCandidates {
+        let prime_index = self.primes.iter()
+            .position(|&p| p >= start)
+            .unwrap_or(self.primes.len());
+
+        // A candidate beyond u64::MAX counts as >= start, and ends the iterator.
+        let base = start - start % self.modulus;
+        let (base, spoke) = match self.residues.iter().position(|&r| base.checked_add(r).is_none_or(|n| n >= start)) {
+            Some(spoke) => (Some(base), spoke),
+            None => (base.checked_add(self.modulus), 0)
+        };
+
+        WheelCandidates { wheel: self, prime_index, base, spoke }
+    }
+}
+
+/// An iterator over the candidates generated by a `Wheel`.
+pub struct WheelCandidates {
+    wheel: &'static Wheel,
+    prime_index: usize,
+    // None once the candidates have gone past u64::MAX.
+    base: Option<u64>,
+    spoke: usize
+}
+
+impl Iterator for WheelCandidates {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        if self.prime_index < self.wheel.primes.len() {
+            self.prime_index += 1;
+            return Some(self.wheel.primes[self.prime_index - 1]);
+        }
+
+        loop {
+            let base = self.base?;
+            let n = match base.checked_add(self.wheel.residues[self.spoke]) {
+                Some(n) => n,
+                None => {
+                    self.base = None;
+                    return None;
+                }
+            };
+            self.spoke += 1;
+            if self.spoke == self.wheel.residues.len() {
+                self.spoke = 0;
+                self.base = base.checked_add(self.wheel.modulus);
+            }
+
+            // 1 is coprime to everything but it is not a prime.
+            if n != 1 {
+                return Some(n);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc;
+
+    #[test]
+    fn candidates_works() {
+        assert_eq!(WHEEL_2.candidates().take(8).collect::<Vec<_>>(), vec![2, 3, 5, 7, 9, 11, 13, 15]);
+        assert_eq!(WHEEL_30.candidates().take(12).collect::<Vec<_>>(), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37]);
+        assert_eq!(WHEEL_210.candidates().take(8).collect::<Vec<_>>(), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+    }
+
+    #[test]
+    fn candidates_are_exactly_the_numbers_coprime_to_the_modulus() {
+        for wheel in &[&WHEEL_2, &WHEEL_30, &WHEEL_210] {
+            let expected = (2..2000)
+                .filter(|&n| wheel.primes().contains(&n) || calc::gcd(n, wheel.modulus) == 1)
+                .collect::<Vec<_>>();
+            let actual = wheel.candidates().take_while(|&n| n < 2000).collect::<Vec<_>>();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn candidates_from_works() {
+        assert_eq!(WHEEL_30.candidates_from(0).next(), Some(2));
+        assert_eq!(WHEEL_30.candidates_from(4).take(3).collect::<Vec<_>>(), vec![5, 7, 11]);
+        assert_eq!(WHEEL_30.candidates_from(30).take(3).collect::<Vec<_>>(), vec![31, 37, 41]);
+        assert_eq!(WHEEL_30.candidates_from(31).next(), Some(31));
+        assert_eq!(WHEEL_30.candidates_from(50).next(), Some(53));
+        assert_eq!(WHEEL_210.candidates_from(200).take(3).collect::<Vec<_>>(), vec![209, 211, 221]);
+    }
+
+    #[test]
+    fn candidates_end_at_the_top_of_the_range() {
+        assert_eq!(WHEEL_30.candidates_from(u64::MAX - 10).collect::<Vec<_>>(),
+                   vec![18_446_744_073_709_551_607, 18_446_744_073_709_551_611, 18_446_744_073_709_551_613]);
+        assert_eq!(WHEEL_2.candidates_from(u64::MAX - 2).collect::<Vec<_>>(),
+                   vec![18_446_744_073_709_551_613, u64::MAX]);
+        assert_eq!(WHEEL_2.candidates_from(u64::MAX).collect::<Vec<_>>(), vec![u64::MAX]);
+        assert_eq!(WHEEL_30.candidates_from(u64::MAX).next(), None);
+        assert_eq!(WHEEL_210.candidates_from(u64::MAX - 1).next(), None);
+    }
+}
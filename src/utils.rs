@@ -12,18 +12,6 @@ pub fn sub_execute(n: usize, msg: &str, f: fn() -> Option<u64>) {
     }
 }
 
-/// Convert a number to a vector of bytes, each of which is guaranteed
-/// to be a decimal digit (0 to 9). This structure is faster than dealing
-/// with Rust Strings or &str slices, which are UTF-8 and cannot be indexed.
-pub fn format_number(n: u64) -> Vec<u8> {
-    format!("{}", n).into_bytes()
-}
-
-/// Check whether a number is a palindrome when converted to a string.
-pub fn is_palindrome_number(n: u64) -> bool {
-    is_palindrome(&format_number(n))
-}
-
 /// Check whether a vector is a palindrome.
 /// An empty vector is considered to be a palindrome.
 pub fn is_palindrome(s: &[u8]) -> bool {
@@ -46,6 +34,7 @@ pub fn is_palindrome(s: &[u8]) -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
 